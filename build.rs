@@ -1,3 +1,31 @@
 fn main() {
-    embuild::espidf::sysenv::output();
+    if std::env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("espidf") {
+        embuild::espidf::sysenv::output();
+    } else {
+        host_hcp();
+    }
+}
+
+// Hôte (feature `sim`) : rien à générer pour le binaire, mais la couche lien HCP (C)
+// est compilée en `libhcp_host.a` pour `tests/hcp.rs`, qui la lie lui-même. esp_log
+// est remplacé par un stub sans sortie.
+fn host_hcp() {
+    const SOURCES: [&str; 2] = ["src/BMLite/src/hcp_tiny.c", "src/BMLite/src/fpc_crc.c"];
+    cc::Build::new()
+        .files(SOURCES)
+        .include("src/BMLite/include")
+        .include("tests/hcp")
+        // Seul le test lie la bibliothèque, pas le binaire
+        .cargo_metadata(false)
+        .compile("hcp_host");
+    println!(
+        "cargo:rustc-link-search=native={}",
+        std::env::var("OUT_DIR").unwrap()
+    );
+    for path in SOURCES
+        .into_iter()
+        .chain(["src/BMLite/include", "tests/hcp/esp_log.h"])
+    {
+        println!("cargo:rerun-if-changed={path}");
+    }
 }
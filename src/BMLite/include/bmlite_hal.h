@@ -80,6 +80,12 @@ void hal_bmlite_reset(bool state);
 fpc_bep_result_t hal_bmlite_spi_write_read(uint8_t *write, uint8_t *read, size_t size,
         bool leave_cs_asserted);
 
+/*
+ * @brief Time spent in hal_bmlite_spi_write_read since boot (cumulated)
+ * @return ::uint64_t [us]
+ */
+uint64_t hal_bmlite_spi_time_get(void);
+
 /*
  * @brief UART write
  * @param[in] Write buffer
@@ -102,6 +108,12 @@ size_t hal_bmlite_uart_read(uint8_t *buff, size_t size);
  */
 bool hal_bmlite_get_status(void);
 
+/*
+ * @brief Read the electrical level of the BM-Lite RST pin (active low)
+ * @return ::bool true if the pin is high (sensor out of reset)
+ */
+bool hal_bmlite_get_reset_level(void);
+
 /**
  * @brief Initializes timebase. Starts system tick counter.
  */
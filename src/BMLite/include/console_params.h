@@ -39,6 +39,14 @@ typedef struct {
    gpio_num_t mosi_pin;
    gpio_num_t irq_pin;
    gpio_num_t spi_clk_pin;
+   // Electrical config of the control lines, applied before the first reset
+   // (current behavior: GPIO_FLOATING, GPIO_DRIVE_CAP_2)
+   gpio_pull_mode_t rst_pull;
+   gpio_pull_mode_t irq_pull;
+   gpio_drive_cap_t rst_drive;
+   // SPI framing (current behavior: mode 0, MSB first)
+   uint8_t spi_mode;      // 0..3, CPOL << 1 | CPHA
+   bool spi_lsb_first;
 } pin_config_t;
 
 typedef struct {
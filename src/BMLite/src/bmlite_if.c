@@ -27,6 +27,9 @@
 #define MAX_CAPTURE_ATTEMPTS 15
 #define MAX_SINGLE_CAPTURE_ATTEMPTS 3
 #define CAPTURE_TIMEOUT 3000
+/* Extra host-side wait so the sensor's own timeout response is read instead of
+ * being left pending and picked up by the next command. */
+#define WAIT_RESPONSE_MARGIN 100
 
 #define exit_if_err(c) { bep_result = c; if(bep_result || chain->bep_result) goto exit; }
 
@@ -126,7 +129,7 @@ fpc_bep_result_t sensor_wait_finger_present(HCP_comm_t *chain, uint16_t timeout)
     uint32_t prev_timeout = chain->phy_rx_timeout;
 
     bmlite_on_start_capture();
-    chain->phy_rx_timeout = timeout;
+    chain->phy_rx_timeout = timeout ? timeout + WAIT_RESPONSE_MARGIN : 0;
     bep_result = bmlite_send_cmd_arg(chain, CMD_WAIT, ARG_FINGER_DOWN, ARG_TIMEOUT, &timeout, sizeof(timeout));
     chain->phy_rx_timeout = prev_timeout;
     bmlite_on_finish_capture();
@@ -139,7 +142,7 @@ fpc_bep_result_t sensor_wait_finger_not_present(HCP_comm_t *chain, uint16_t time
     fpc_bep_result_t bep_result;
     uint32_t prev_timeout = chain->phy_rx_timeout;
 
-    chain->phy_rx_timeout = timeout;
+    chain->phy_rx_timeout = timeout ? timeout + WAIT_RESPONSE_MARGIN : 0;
     bep_result = bmlite_send_cmd_arg(chain, CMD_WAIT, ARG_FINGER_UP, ARG_TIMEOUT, &timeout, sizeof(timeout));
     chain->phy_rx_timeout = prev_timeout;
 
@@ -14,6 +14,8 @@
 
 static spi_device_handle_t spi_handle;
 static pin_config_t *pins;
+// Temps cumulé passé dans les transferts SPI (profilage, voir hal_bmlite_spi_time_get)
+static uint64_t spi_time_us;
 
 fpc_bep_result_t hal_board_deinit(void *params)
 {
@@ -54,6 +56,20 @@ fpc_bep_result_t hal_board_deinit(void *params)
     return FPC_BEP_RESULT_OK;
 }
 
+// Défait une init partielle : sur échec, hal_board_init ne garde ni bus ni pointeur,
+// l'appelant peut libérer ses paramètres et réessayer (autre câblage)
+static void board_init_rollback(void)
+{
+    if (spi_handle) {
+        spi_bus_remove_device(spi_handle);
+        spi_handle = NULL;
+    }
+    spi_bus_free(pins->spi_host);
+    gpio_reset_pin(pins->rst_pin);
+    gpio_reset_pin(pins->irq_pin);
+    pins = NULL;
+}
+
 fpc_bep_result_t hal_board_init(void *params)
 {
     
@@ -81,7 +97,8 @@ fpc_bep_result_t hal_board_init(void *params)
     };
 
     spi_device_interface_config_t devcfg = {
-        .mode = 0,
+        .mode = pins->spi_mode,
+        .flags = pins->spi_lsb_first ? (SPI_DEVICE_TXBIT_LSBFIRST | SPI_DEVICE_RXBIT_LSBFIRST) : 0,
         .clock_speed_hz = p->baudrate,
         .spics_io_num = pins->cs_n_pin,
         .queue_size = 1,
@@ -97,21 +114,29 @@ fpc_bep_result_t hal_board_init(void *params)
     ret = spi_bus_add_device(pins->spi_host, &devcfg, &spi_handle);
     if (ret != ESP_OK) {
         ESP_LOGE(TAG, "Failed to add SPI device");
-        spi_bus_free(pins->spi_host);
+        spi_handle = NULL;
+        board_init_rollback();
         return FPC_BEP_RESULT_INTERNAL_ERROR;
     }
 
-    // Init RST Pin
+    // Init RST Pin (entrée gardée active : niveau relisible par hal_bmlite_get_reset_level)
     gpio_config_t io_conf = {
         .pin_bit_mask = (1ULL << pins->rst_pin),
-        .mode = GPIO_MODE_OUTPUT,
+        .mode = GPIO_MODE_INPUT_OUTPUT,
         .pull_up_en = GPIO_PULLUP_DISABLE,
         .pull_down_en = GPIO_PULLDOWN_DISABLE,
         .intr_type = GPIO_INTR_DISABLE,
     };
 
     ret = gpio_config(&io_conf);
+    if (ret == ESP_OK) {
+        ret = gpio_set_pull_mode(pins->rst_pin, pins->rst_pull);
+    }
+    if (ret == ESP_OK) {
+        ret = gpio_set_drive_capability(pins->rst_pin, pins->rst_drive);
+    }
     if (ret != ESP_OK) {
+        board_init_rollback();
         return FPC_BEP_RESULT_INTERNAL_ERROR;
     }
 
@@ -120,7 +145,11 @@ fpc_bep_result_t hal_board_init(void *params)
     io_conf.mode = GPIO_MODE_INPUT;
     
     ret = gpio_config(&io_conf);
+    if (ret == ESP_OK) {
+        ret = gpio_set_pull_mode(pins->irq_pin, pins->irq_pull);
+    }
     if (ret != ESP_OK) {
+        board_init_rollback();
         return FPC_BEP_RESULT_INTERNAL_ERROR;
     }
 
@@ -142,6 +171,11 @@ bool hal_bmlite_get_status(void)
     return gpio_get_level(pins->irq_pin) == 1;  // Active High
 }
 
+bool hal_bmlite_get_reset_level(void)
+{
+    return gpio_get_level(pins->rst_pin) == 1;  // Active Low : haut = hors reset
+}
+
 fpc_bep_result_t hal_bmlite_spi_write_read(uint8_t *write, uint8_t *read, size_t size, bool leave_cs_asserted)
 {
     if (size == 0) {
@@ -155,13 +189,20 @@ fpc_bep_result_t hal_bmlite_spi_write_read(uint8_t *write, uint8_t *read, size_t
         .flags = leave_cs_asserted ? SPI_TRANS_CS_KEEP_ACTIVE : 0,
     };
 
+    int64_t start = esp_timer_get_time();
     esp_err_t ret = spi_device_transmit(spi_handle, &t);
+    spi_time_us += esp_timer_get_time() - start;
     if (ret != ESP_OK) {
         return FPC_BEP_RESULT_IO_ERROR;
     }
     return FPC_BEP_RESULT_OK;
 }
 
+uint64_t hal_bmlite_spi_time_get(void)
+{
+    return spi_time_us;
+}
+
 void hal_timebase_init(void) {} // Unnecessary on ESP32
 
 hal_tick_t hal_timebase_get_tick(void)
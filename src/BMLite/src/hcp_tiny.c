@@ -192,6 +192,10 @@ fpc_bep_result_t bmlite_receive(HCP_comm_t *hcp_comm)
     return com_result;
 }
 
+// One link frame: header, then payload + CRC. The SPI master clocks every byte, so
+// each read returns the requested size or fails: no partial frame to accumulate.
+// Packets split over several frames are reassembled by bmlite_receive from the
+// transport sequence numbers.
 static fpc_bep_result_t _rx_link(HCP_comm_t *hcp_comm)
 {
     // Get size, msg and CRC
@@ -213,7 +217,14 @@ static fpc_bep_result_t _rx_link(HCP_comm_t *hcp_comm)
         return FPC_BEP_RESULT_IO_ERROR;
     }
         
-    hcp_comm->read(size + 4, hcp_comm->txrx_buffer + 4, 100);
+    // Payload + CRC: a failed or timed-out read leaves stale bytes in txrx_buffer,
+    // so report it instead of letting the CRC check fail on garbage.
+    result = hcp_comm->read(size + 4, hcp_comm->txrx_buffer + 4, 100);
+    if (result) {
+        ESP_LOGW(TAG, "Short read: payload of %d bytes not received (%d).\n", size, result);
+        bmlite_on_error(BMLITE_ERROR_SEND_CMD, result);
+        return result;
+    }
 
     uint32_t crc = *(uint32_t *)(hcp_comm->txrx_buffer + 4 + size);
     uint32_t crc_calc = fpc_crc(0, hcp_comm->txrx_buffer+4, size);
@@ -0,0 +1,120 @@
+#![forbid(unsafe_code)]
+
+// Mode hybride (feature `std`) : attente du doigt en `async`, identification synchrone.
+//
+// L'attente tourne dans une tâche dédiée, par tranches de `WAIT_SLICE_MS` verrou
+// relâché entre deux tranches, et réveille le `Waker` à la fin : aucun réacteur
+// n'est nécessaire, n'importe quel exécuteur convient (`block_on` d'esp-idf-svc,
+// futures, embassy sur std, ...). La détection reste celle du firmware (commande
+// WAIT FINGER_DOWN) : pas d'ISR GPIO, mais la tâche appelante n'est pas bloquée.
+
+use anyhow::Result;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::global::with_sensor;
+use super::{CancelToken, FingerprintError};
+
+// Tranche d'attente firmware, verrou tenu
+const WAIT_SLICE_MS: u32 = 100;
+// Timeout de l'identification une fois le doigt détecté
+const IDENTIFY_TIMEOUT_MS: u32 = 2_000;
+const WAIT_STACK_SIZE: usize = 6 * 1024;
+
+#[derive(Default)]
+struct Shared {
+    done: Option<Result<bool>>,
+    waker: Option<Waker>,
+}
+
+// Future de l'attente : la tâche d'attente s'arrête si le future est abandonné
+struct FingerWait {
+    shared: Arc<Mutex<Shared>>,
+    cancel: CancelToken,
+}
+
+impl FingerWait {
+    fn spawn(timeout_ms: u32) -> Result<Self> {
+        let shared = Arc::new(Mutex::new(Shared::default()));
+        let cancel = CancelToken::new();
+
+        let (state, token) = (shared.clone(), cancel.clone());
+        thread::Builder::new()
+            .name("fp-wait".into())
+            .stack_size(WAIT_STACK_SIZE)
+            .spawn(move || {
+                let res = wait_sliced(timeout_ms, &token);
+                let mut state = state.lock().unwrap();
+                state.done = Some(res);
+                if let Some(waker) = state.waker.take() {
+                    waker.wake();
+                }
+            })?;
+        Ok(Self { shared, cancel })
+    }
+}
+
+impl Future for FingerWait {
+    type Output = Result<bool>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.shared.lock().unwrap();
+        match state.done.take() {
+            Some(res) => Poll::Ready(res),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for FingerWait {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+// Attente du doigt (0 = infini), verrou capteur relâché entre deux tranches
+fn wait_sliced(timeout_ms: u32, cancel: &CancelToken) -> Result<bool> {
+    let deadline =
+        (timeout_ms > 0).then(|| Instant::now() + Duration::from_millis(timeout_ms.into()));
+    loop {
+        if cancel.is_cancelled() {
+            return Err(FingerprintError::Cancelled.into());
+        }
+        let slice = match deadline {
+            Some(deadline) => {
+                let rest = deadline.saturating_duration_since(Instant::now());
+                if rest.is_zero() {
+                    return Ok(false);
+                }
+                (rest.as_millis() as u32).min(WAIT_SLICE_MS)
+            }
+            None => WAIT_SLICE_MS,
+        };
+        if with_sensor(|ctx| ctx.wait_for_finger(slice.max(1)))? {
+            return Ok(true);
+        }
+    }
+}
+
+/// Attend un doigt sans bloquer la tâche appelante, puis l'identifie de façon
+/// synchrone. Retourne l'ID reconnu, `None` si le doigt ne correspond à aucun
+/// template, `FingerprintError::Timeout` si aucun doigt n'est posé à temps.
+///
+/// Exécuteur : aucun réacteur requis (réveil par une tâche dédiée), mais
+/// l'identification bloque le thread de l'exécuteur (quelques centaines de ms,
+/// jusqu'à 2 s) : sur un exécuteur mono-thread, les autres tâches attendent.
+/// Abandonner le future arrête l'attente (au plus une tranche de 100 ms).
+pub async fn await_finger_then_identify(timeout_ms: u32) -> Result<Option<u16>> {
+    if !FingerWait::spawn(timeout_ms)?.await? {
+        return Err(FingerprintError::Timeout("sensor_wait_finger_present").into());
+    }
+    let best = with_sensor(|ctx| ctx.identify_candidates(1, IDENTIFY_TIMEOUT_MS))?;
+    Ok(best.first().map(|&(tid, _)| tid))
+}
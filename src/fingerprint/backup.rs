@@ -0,0 +1,205 @@
+#![forbid(unsafe_code)]
+
+// Sauvegarde / restauration de tous les templates en un seul blob versionné.
+//
+// Format (entiers little-endian) :
+//   en-tête : "FPBK" | version u8 | nombre d'entrées u16
+//   entrée  : id u16 | longueur label u16 | label UTF-8 | longueur template u16 | template
+//   fin     : CRC32 (u32) de tout ce qui précède
+//
+// Les templates sont en clair (même contenu que `template_export`) : chiffrer le
+// blob côté appelant s'il quitte l'appareil.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use anyhow::Result;
+
+use super::bep;
+use super::error::FingerprintError;
+#[cfg(feature = "enroll")]
+use super::integrity::validate_template_bytes;
+use super::labels::Label;
+use super::sensor::{not_initialized, SensorCtx};
+use super::table::IdTable;
+
+pub(super) const MAGIC: &[u8; 4] = b"FPBK";
+const VERSION: u8 = 1;
+#[cfg(feature = "enroll")]
+const HEADER_LEN: usize = MAGIC.len() + 1 + 2;
+#[cfg(feature = "enroll")]
+const CRC_LEN: usize = 4;
+
+fn put_u16(out: &mut Vec<u8>, v: u16) {
+    out.extend_from_slice(&v.to_le_bytes());
+}
+
+// Blob complet (en-tête, entrées, CRC) pour `templates`, triés par ID
+fn encode(templates: &[(u16, &[u8])], labels: &IdTable<Label>) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    out.push(VERSION);
+    put_u16(&mut out, templates.len() as u16);
+    for &(id, template) in templates {
+        // Au plus `MAX_LABEL_LEN` octets : la longueur tient sur 16 bits
+        let label = labels.get(id);
+        let label = label.as_ref().map_or("", Label::as_str);
+
+        put_u16(&mut out, id);
+        put_u16(&mut out, label.len() as u16);
+        out.extend_from_slice(label.as_bytes());
+        put_u16(&mut out, template.len() as u16);
+        out.extend_from_slice(template);
+    }
+    let crc = bep::crc32(&out);
+    out.extend_from_slice(&crc.to_le_bytes());
+    out
+}
+
+// Lecture séquentielle du blob ; toute lecture hors limites est une erreur d'intégrité
+#[cfg(feature = "enroll")]
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+#[cfg(feature = "enroll")]
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.data.len() < len {
+            return Err(FingerprintError::Integrity.into());
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        let b = self.take(2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+}
+
+#[cfg(feature = "enroll")]
+struct Entry<'a> {
+    id: u16,
+    label: Option<String>,
+    template: &'a [u8],
+}
+
+// Vérifie en-tête et CRC puis découpe les entrées, sans rien écrire sur le capteur
+#[cfg(feature = "enroll")]
+fn parse(blob: &[u8]) -> Result<Vec<Entry<'_>>> {
+    if blob.len() < HEADER_LEN + CRC_LEN || &blob[..MAGIC.len()] != MAGIC {
+        return Err(FingerprintError::Integrity.into());
+    }
+    let (body, crc) = blob.split_at(blob.len() - CRC_LEN);
+    if bep::crc32(body) != u32::from_le_bytes([crc[0], crc[1], crc[2], crc[3]]) {
+        return Err(FingerprintError::Integrity.into());
+    }
+    let version = body[MAGIC.len()];
+    if version != VERSION {
+        return Err(FingerprintError::UnsupportedBackup(version).into());
+    }
+
+    let mut r = Reader {
+        data: &body[MAGIC.len() + 1..],
+    };
+    let count = r.u16()?;
+    let mut entries = Vec::with_capacity(count.into());
+    for _ in 0..count {
+        let id = r.u16()?;
+        let label_len = r.u16()?.into();
+        let label = match r.take(label_len)? {
+            [] => None,
+            raw => Some(String::from_utf8(raw.to_vec()).map_err(|_| FingerprintError::Integrity)?),
+        };
+        let template_len = r.u16()?.into();
+        let template = r.take(template_len)?;
+        validate_template_bytes(template)?;
+        entries.push(Entry {
+            id,
+            label,
+            template,
+        });
+    }
+    if !r.data.is_empty() {
+        return Err(FingerprintError::Integrity.into());
+    }
+    Ok(entries)
+}
+
+impl SensorCtx {
+    /// Sérialise tous les templates présents (ID, label éventuel, contenu) dans un
+    /// blob versionné protégé par CRC32, à restaurer avec `import_all`.
+    ///
+    /// Avec le miroir hôte (`enable_mirror`), le blob est construit depuis la RAM, sans
+    /// SPI ; un miroir devenu incomplet est d'abord relu depuis le capteur. Le capteur
+    /// doit être initialisé dans les deux cas.
+    pub fn export_all(&mut self) -> Result<Vec<u8>> {
+        if self.chain.is_none() {
+            return Err(not_initialized());
+        }
+        if self.mirror.is_enabled() && self.mirror.templates().is_none() {
+            self.sync_mirror()?;
+        }
+        let out = match self.mirror.templates() {
+            Some(mirrored) => {
+                let templates: Vec<(u16, &[u8])> =
+                    mirrored.iter().map(|(&id, t)| (id, t.as_slice())).collect();
+                encode(&templates, &self.labels)
+            }
+            None => {
+                let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+                let ids = chain.template_ids()?;
+                self.enrolled.set(&ids);
+                let mut exported = Vec::with_capacity(ids.len());
+                for id in ids {
+                    exported.push((id, chain.template_export(id)?));
+                }
+                let templates: Vec<(u16, &[u8])> =
+                    exported.iter().map(|(id, t)| (*id, t.as_slice())).collect();
+                encode(&templates, &self.labels)
+            }
+        };
+
+        fp_info!(target: self.log_target, "Sauvegarde : {} octets", out.len());
+        Ok(out)
+    }
+
+    /// Restaure un blob produit par `export_all`. Retourne le nombre de templates écrits.
+    ///
+    /// Le blob est entièrement vérifié (en-tête, CRC, structure, puis chaque template
+    /// avec `validate_template_bytes`) avant toute écriture : s'il est invalide,
+    /// `FingerprintError::Integrity`, `UnsupportedBackup` ou `IncompatibleTemplate`, et
+    /// le capteur n'est pas modifié. Un ID déjà occupé est remplacé, les autres templates
+    /// du capteur sont conservés.
+    #[cfg(feature = "enroll")]
+    pub fn import_all(&mut self, blob: &[u8]) -> Result<u16> {
+        self.check_unlocked("import_all")?;
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+        let entries = parse(blob)?;
+
+        let existing = chain.template_ids()?;
+        for entry in &entries {
+            if existing.contains(&entry.id) {
+                chain.template_remove(entry.id)?;
+            }
+            chain.template_import(entry.id, entry.template)?;
+            self.enrolled.insert(entry.id);
+            self.mirror.store(entry.id, entry.template);
+            self.qualities.remove(entry.id);
+            self.checksums.remove(entry.id);
+            self.stats.remove(entry.id);
+            match &entry.label {
+                Some(label) => {
+                    if !self.labels.insert(entry.id, Label::new(label)) {
+                        fp_warn!(target: self.log_target, "Template {}: label ignoré, table pleine", entry.id);
+                    }
+                }
+                None => self.labels.remove(entry.id),
+            }
+        }
+
+        fp_info!(target: self.log_target, "Restauration : {} templates", entries.len());
+        Ok(entries.len() as u16)
+    }
+}
@@ -0,0 +1,95 @@
+#![forbid(unsafe_code)]
+
+// Banc de comparaison en production : les mêmes templates de référence sont joués
+// sur le capteur de référence et sur chaque unité testée, et les rapports comparés.
+//
+// Chaque template est chargé en RAM BM-Lite le temps d'une vérification
+// (`verify_transient`) : l'unité testée n'est jamais écrite en flash. Le firmware ne
+// renvoie pas de score de match : `score` reste `None`, la comparaison porte sur les
+// matchs et les latences. Un doigt (ou un faux doigt de test) correspondant aux
+// templates doit être posé à chaque vérification.
+
+use alloc::vec::Vec;
+use anyhow::Result;
+
+use super::error::FingerprintError;
+use super::sensor::{not_initialized, OpTiming, SensorCtx};
+
+/// Attente du doigt par template dans `benchmark_against`.
+pub const BENCH_TIMEOUT_MS: u32 = 10_000;
+
+/// Résultat d'une vérification de `benchmark_against`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchEntry {
+    /// Position du template dans `reference_templates`
+    pub index: usize,
+    pub matched: bool,
+    /// Aucun doigt posé avant `BENCH_TIMEOUT_MS` (`matched` est alors faux)
+    pub timed_out: bool,
+    /// Score de match (`None` : non rapporté par le BM-Lite)
+    pub score: Option<u16>,
+    pub timing: OpTiming,
+}
+
+/// Bilan de `benchmark_against`, à comparer à celui du capteur de référence.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BenchReport {
+    pub entries: Vec<BenchEntry>,
+    pub matched: usize,
+    pub timeouts: usize,
+    /// Durée moyenne d'une vérification aboutie (hors timeouts), en µs
+    pub mean_latency_us: u64,
+}
+
+impl SensorCtx {
+    /// Vérifie le doigt posé contre chaque template de `reference_templates` (format
+    /// `template_export`) et relève matchs et latences. Un timeout est noté et le
+    /// banc continue ; toute autre erreur (lien, template refusé) l'interrompt.
+    pub fn benchmark_against(&mut self, reference_templates: &[Vec<u8>]) -> Result<BenchReport> {
+        if self.chain.is_none() {
+            return Err(not_initialized());
+        }
+
+        let mut report = BenchReport::default();
+        let mut total_us = 0;
+        for (index, template) in reference_templates.iter().enumerate() {
+            let res = self.timed(|ctx| ctx.verify_transient(template, BENCH_TIMEOUT_MS));
+            let timing = self.last_timing.unwrap_or_default();
+            let (matched, timed_out) = match res {
+                Ok(matched) => (matched, false),
+                Err(e)
+                    if matches!(FingerprintError::of(&e), Some(FingerprintError::Timeout(_))) =>
+                {
+                    (false, true)
+                }
+                Err(e) => return Err(e),
+            };
+
+            if timed_out {
+                report.timeouts += 1;
+            } else {
+                total_us += timing.total_us;
+            }
+            report.matched += usize::from(matched);
+            report.entries.push(BenchEntry {
+                index,
+                matched,
+                timed_out,
+                score: None,
+                timing,
+            });
+        }
+
+        let measured = (report.entries.len() - report.timeouts) as u64;
+        report.mean_latency_us = total_us.checked_div(measured).unwrap_or(0);
+        fp_info!(
+            target: self.log_target,
+            "Banc : {}/{} matchs, {} timeouts, {} µs en moyenne",
+            report.matched,
+            report.entries.len(),
+            report.timeouts,
+            report.mean_latency_us
+        );
+        Ok(report)
+    }
+}
@@ -0,0 +1,882 @@
+// Wrappers sûrs autour du C BM-Lite : c'est le seul module du crate qui contient du `unsafe`.
+//
+// Invariants établis par `init_board` et maintenus tant que `Board` / `Chain` existent :
+// - `HCP_comm_t`, ses buffers (`pkt_buffer`, `txrx_buffer`), `Params` et `pin_config_t`
+//   sont alloués par `Box` et ne sont libérés que par `deinit_board`, qui consomme
+//   `Board` et `Chain` après un `platform_deinit` réussi (adresse stable, toujours valide) ;
+// - `platform_init` a réussi, donc les callbacks `read` / `write` de la chaîne sont posés ;
+// - l'accès est exclusif : toutes les méthodes prennent `&mut self`, et le `SensorCtx`
+//   propriétaire est lui-même sérialisé par un `SensorLock`.
+//
+// Panics et frontière FFI : les callbacks `read` / `write` sont ceux d'esp_hal.c
+// (posés par `platform_init`). Seule exception, la trace des paquets
+// (`Chain::set_packet_trace`) intercale les trampolines `trace_write` / `trace_read`,
+// qui appellent d'abord le callback C d'origine puis journalisent le paquet (`trace`).
+// Sous `std`, la journalisation passe par `catch_unwind` : un panic s'arrête là et ne
+// remonte pas dans le code C appelant. Sans `std`, pas de `catch_unwind` ni de
+// déroulement : un panic aboutit au `#[panic_handler]`, qui ne rend pas la main.
+// Symétriquement, une faute côté C (assert, accès invalide) passe par le panic handler
+// ESP-IDF et redémarre la puce : pas de récupération possible ici, le verrou capteur
+// n'est donc jamais laissé empoisonné par un appel FFI.
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+use anyhow::{anyhow, Result};
+use core::ffi::c_void;
+use core::fmt;
+use core::ptr::{self, NonNull};
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+#[cfg(feature = "nvs")]
+use esp_idf_svc::nvs::{EspDefaultNvsPartition, EspNvs, NvsDefault};
+use esp_idf_svc::sys::bmlite::{
+    // SPI types et constantes
+    interface_t_SPI_INTERFACE,
+    pin_config_t,
+    spi_host_device_t_SPI2_HOST,
+    spi_host_device_t_SPI3_HOST,
+
+    // Configuration électrique des GPIO
+    gpio_drive_cap_t,
+    gpio_drive_cap_t_GPIO_DRIVE_CAP_0,
+    gpio_drive_cap_t_GPIO_DRIVE_CAP_1,
+    gpio_drive_cap_t_GPIO_DRIVE_CAP_2,
+    gpio_drive_cap_t_GPIO_DRIVE_CAP_3,
+    gpio_pull_mode_t,
+    gpio_pull_mode_t_GPIO_FLOATING,
+    gpio_pull_mode_t_GPIO_PULLDOWN_ONLY,
+    gpio_pull_mode_t_GPIO_PULLUP_ONLY,
+
+    // Plateforme BM-Lite
+    platform_bmlite_reset,
+    platform_deinit,
+    platform_init,
+
+    // Arguments HCP
+    fpc_hcp_arg_ARG_COUNT,
+    fpc_hcp_arg_ARG_EXTRACT,
+    fpc_hcp_arg_ARG_ID,
+    fpc_hcp_arg_ARG_MATCH,
+    fpc_hcp_arg_ARG_NONE,
+
+    // Commandes HCP
+    fpc_hcp_cmd_CMD_IMAGE,
+    fpc_hcp_cmd_CMD_MATCH,
+
+    // Résultats / status
+    fpc_bep_result_t_FPC_BEP_RESULT_OK,
+
+    // MTU fourni par ESP-IDF
+    MTU,
+};
+
+// Commandes d'enrôlement
+#[cfg(feature = "enroll")]
+use esp_idf_svc::sys::bmlite::{
+    fpc_hcp_arg_ARG_ADD, fpc_hcp_arg_ARG_FINISH, fpc_hcp_arg_ARG_START, fpc_hcp_cmd_CMD_ENROLL,
+};
+
+use super::error::{
+    FingerprintError, BEP_RESULT_INVALID_ARGUMENT, BEP_RESULT_INVALID_CALIBRATION, BEP_RESULT_IO_ERROR, BEP_RESULT_TIMEOUT,
+};
+use super::link::LinkMonitor;
+use super::pins::{BitOrder, DriveStrength, PinConfig, Pull, SpiHost};
+use super::sensor::{ChainInfo, HcpArgView, MAX_TEMPLATE_LEN};
+use super::ffi::*;
+
+// Réponses en attente lues au plus par `reset_link` (IRQ restée haute)
+const MAX_STALE_RESPONSES: u8 = 4;
+
+// Taille de l'identifiant unique renvoyé par bep_unique_id_get
+pub(super) const UNIQUE_ID_LEN: usize = 12;
+// Buffer de bep_version (chaîne tronquée au-delà)
+const VERSION_LEN: usize = 64;
+
+// Octets vidés au plus par transfert tracé (les transferts d'image font plusieurs ko)
+const TRACE_MAX_BYTES: usize = 64;
+// Target `log` de la trace, filtrable à part des autres messages du module
+const TRACE_TARGET: &str = "fingerprint::hcp";
+
+// ======================================================
+// Helper pour erreurs
+// ======================================================
+
+fn check_bep(res: i32, what: &'static str) -> Result<()> {
+    if res == fpc_bep_result_t_FPC_BEP_RESULT_OK {
+        Ok(())
+    } else {
+        Err(FingerprintError::from_code(what, res).into())
+    }
+}
+
+// ======================================================
+// Création des structs C (Params + HCP_comm + pin_config)
+// ======================================================
+
+/// Propriétaire des paramètres passés à `platform_init` (console_initparams_t + pins).
+pub(super) struct Board {
+    params: NonNull<Params>,
+    pins: NonNull<pin_config_t>,
+}
+
+/// Chaîne HCP initialisée : seul point d'entrée vers les commandes `bep_*`.
+pub(super) struct Chain {
+    raw: NonNull<HCP_comm_t>,
+    link: LinkMonitor,
+    // Trampolines de trace posés (voir `set_packet_trace`)
+    traced: bool,
+}
+
+// SAFETY: les pointeurs désignent des allocations propres au contexte, jamais partagées
+// avec un autre thread côté C ; l'accès concurrent est empêché par `&mut self` + SensorLock.
+unsafe impl Send for Board {}
+unsafe impl Send for Chain {}
+
+// Tailles des buffers HCP : `deinit_board` doit les libérer avec le même type
+const PKT_BUFFER_LEN: usize = MAX_TEMPLATE_LEN;
+const TXRX_BUFFER_LEN: usize = MTU as usize;
+
+fn pull_mode(pull: Pull) -> gpio_pull_mode_t {
+    match pull {
+        Pull::Floating => gpio_pull_mode_t_GPIO_FLOATING,
+        Pull::Up => gpio_pull_mode_t_GPIO_PULLUP_ONLY,
+        Pull::Down => gpio_pull_mode_t_GPIO_PULLDOWN_ONLY,
+    }
+}
+
+fn drive_cap(drive: DriveStrength) -> gpio_drive_cap_t {
+    match drive {
+        DriveStrength::Weakest => gpio_drive_cap_t_GPIO_DRIVE_CAP_0,
+        DriveStrength::Weak => gpio_drive_cap_t_GPIO_DRIVE_CAP_1,
+        DriveStrength::Medium => gpio_drive_cap_t_GPIO_DRIVE_CAP_2,
+        DriveStrength::Strongest => gpio_drive_cap_t_GPIO_DRIVE_CAP_3,
+    }
+}
+
+fn alloc_config(pins: &PinConfig) -> (NonNull<Params>, NonNull<pin_config_t>, NonNull<HCP_comm_t>) {
+    let pkt_buffer = Box::into_raw(Box::new([0u8; PKT_BUFFER_LEN])) as *mut u8;
+    let txrx_buffer = Box::into_raw(Box::new([0u8; TXRX_BUFFER_LEN])) as *mut u8;
+
+    let chain = NonNull::from(Box::leak(Box::new(HCP_comm_t {
+        write: None,
+        read: None,
+        phy_rx_timeout: 2000,
+        pkt_buffer,
+        pkt_size_max: PKT_BUFFER_LEN as u32,
+        pkt_size: 0,
+        txrx_buffer,
+        arg: HCP_arg_t { size: 0, data: ptr::null_mut() },
+        bep_result: 0,
+    })));
+
+    let pins = NonNull::from(Box::leak(Box::new(pin_config_t {
+        spi_host: match pins.spi_host {
+            SpiHost::Spi2 => spi_host_device_t_SPI2_HOST,
+            SpiHost::Spi3 => spi_host_device_t_SPI3_HOST,
+        },
+        cs_n_pin: pins.cs,
+        miso_pin: pins.miso,
+        rst_pin: pins.rst,
+        mosi_pin: pins.mosi,
+        irq_pin: pins.irq,
+        spi_clk_pin: pins.clk,
+        rst_pull: pull_mode(pins.rst_line.pull),
+        irq_pull: pull_mode(pins.irq_line.pull),
+        rst_drive: drive_cap(pins.rst_line.drive),
+        spi_mode: pins.spi_mode as u8,
+        spi_lsb_first: pins.bit_order == BitOrder::LsbFirst,
+    })));
+
+    let params = NonNull::from(Box::leak(Box::new(Params {
+        iface: interface_t_SPI_INTERFACE,
+        port: ptr::null_mut(),
+        baudrate: 1_000_000, // plus stable pour test
+        timeout: 3000,
+        hcp_comm: chain.as_ptr(),
+        pins: pins.as_ptr(),
+    })));
+
+    (params, pins, chain)
+}
+
+// Buffers alloués par alloc_config, notés avant platform_init pour la vérification
+struct Buffers {
+    pkt: *mut u8,
+    txrx: *mut u8,
+}
+
+impl Buffers {
+    fn of(chain: &HCP_comm_t) -> Self {
+        Self {
+            pkt: chain.pkt_buffer,
+            txrx: chain.txrx_buffer,
+        }
+    }
+}
+
+/// Alloue la configuration et initialise la plateforme (SPI, GPIO, reset matériel).
+pub(super) fn init_board(pins: &PinConfig) -> Result<(Board, Chain)> {
+    pins.validate()?;
+    let (params, pins, chain) = alloc_config(pins);
+    // SAFETY: lecture seule d'une allocation valide, pas encore partagée avec le C.
+    let buffers = Buffers::of(unsafe { chain.as_ref() });
+
+    // SAFETY: `params` est un console_initparams_t complet dont `hcp_comm` et `pins`
+    // pointent vers des allocations vivantes (alloc_config).
+    if let Err(e) = check_bep(unsafe { platform_init(params.as_ptr().cast()) }, "platform_init") {
+        // SAFETY: sur échec, hal_board_init a rendu bus et GPIO et ne garde aucun
+        // pointeur : les allocations d'alloc_config ne sont plus partagées
+        unsafe { free_config(params, pins, chain) };
+        return Err(e);
+    }
+
+    let (board, chain) = (Board { params, pins }, Chain { raw: chain, link: LinkMonitor::new(), traced: false });
+    if let Err(field) = chain.validate(&buffers) {
+        fp_error!("BM-Lite: chaîne HCP invalide après platform_init ({field})");
+        chain.restore_buffers(&buffers);
+        // Sur échec, le C garde peut-être des pointeurs : `Board` / `Chain` sont
+        // abandonnés sans libération (fuite volontaire)
+        if let Err((_, _, e)) = deinit_board(board, chain) {
+            fp_error!("BM-Lite: platform_deinit impossible ({e}), allocations conservées");
+        }
+        return Err(FingerprintError::InvalidChain(field).into());
+    }
+    Ok((board, chain))
+}
+
+/// Libère le SPI / les GPIO puis toutes les allocations de `init_board`.
+///
+/// En cas d'échec de `platform_deinit`, le C peut encore référencer les pins : rien
+/// n'est libéré et `Board` / `Chain` sont rendus à l'appelant avec l'erreur.
+pub(super) fn deinit_board(board: Board, chain: Chain) -> core::result::Result<(), (Board, Chain, anyhow::Error)> {
+    // SAFETY: `params` est celui passé à platform_init (invariants du module).
+    let res = unsafe { platform_deinit(board.params.as_ptr().cast()) };
+    if let Err(e) = check_bep(res, "platform_deinit") {
+        return Err((board, chain, e));
+    }
+
+    // SAFETY: platform_deinit a réussi, le C ne garde plus aucun pointeur (pins remis
+    // à NULL) ; `Board` / `Chain` sont consommés, donc plus aucun accès possible après
+    // libération.
+    unsafe { free_config(board.params, board.pins, chain.raw) };
+    Ok(())
+}
+
+// Libère les allocations d'alloc_config.
+//
+// SAFETY (appelant) : les trois pointeurs viennent du même appel à alloc_config, et ni
+// le C ni le Rust ne les utilisent plus.
+unsafe fn free_config(params: NonNull<Params>, pins: NonNull<pin_config_t>, chain: NonNull<HCP_comm_t>) {
+    let raw = Box::from_raw(chain.as_ptr());
+    drop(Box::from_raw(raw.pkt_buffer.cast::<[u8; PKT_BUFFER_LEN]>()));
+    drop(Box::from_raw(raw.txrx_buffer.cast::<[u8; TXRX_BUFFER_LEN]>()));
+    drop(Box::from_raw(pins.as_ptr()));
+    drop(Box::from_raw(params.as_ptr()));
+}
+
+// ======================================================
+// Plateforme (reset, temps)
+// ======================================================
+
+/// Reset matériel du BM-Lite via la broche RST.
+pub(super) fn hw_reset(_board: &mut Board) {
+    // SAFETY: n'utilise que les pins enregistrées par platform_init ; l'emprunt de
+    // `Board` garantit que la plateforme est initialisée.
+    unsafe { platform_bmlite_reset() }
+}
+
+/// Maintient (`true`) ou relâche le BM-Lite en reset, sans attente de démarrage.
+pub(super) fn hold_reset(_board: &mut Board, held: bool) {
+    // SAFETY: n'utilise que les pins enregistrées par platform_init ; l'emprunt de
+    // `Board` garantit que la plateforme est initialisée.
+    unsafe { hal_bmlite_reset(held) }
+}
+
+/// Niveaux actuels des lignes IRQ et RST (`true` = haut), lus sur les GPIO.
+pub(super) fn control_lines(_board: &Board) -> (bool, bool) {
+    // SAFETY: lectures GPIO des pins enregistrées par platform_init ; l'emprunt de
+    // `Board` garantit que la plateforme est initialisée.
+    unsafe { (hal_bmlite_get_status(), hal_bmlite_get_reset_level()) }
+}
+
+/// Attente bloquante (vTaskDelay côté ESP).
+pub(super) fn busy_wait(ms: u32) {
+    // SAFETY: aucune précondition, simple délai FreeRTOS.
+    unsafe { hal_timebase_busy_wait(ms) }
+}
+
+/// CRC32 (même algorithme que le lien HCP) sur `data`.
+pub(super) fn crc32(data: &[u8]) -> u32 {
+    // SAFETY: `data` est un slice valide de `data.len()` octets, lu uniquement.
+    unsafe { fpc_crc(0, data.as_ptr().cast(), data.len() as u32) }
+}
+
+/// Remplit `buf` avec le RNG matériel (vrai aléa si le Wi-Fi/BT ou le bootloader RNG est actif).
+#[cfg(feature = "crypto")]
+pub(super) fn fill_random(buf: &mut [u8]) {
+    // SAFETY: `buf` est un slice valide de `buf.len()` octets, écrit uniquement.
+    unsafe { esp_fill_random(buf.as_mut_ptr().cast(), buf.len()) }
+}
+
+/// Temps écoulé depuis le boot, en ms.
+pub(super) fn tick_ms() -> u64 {
+    // SAFETY: aucune précondition, lecture de esp_timer.
+    unsafe { hal_timebase_get_tick() }
+}
+
+/// Temps écoulé depuis le boot, en µs.
+pub(super) fn tick_us() -> u64 {
+    // SAFETY: aucune précondition, lecture de esp_timer.
+    unsafe { esp_timer_get_time() as u64 }
+}
+
+/// Temps cumulé passé dans les transferts SPI depuis le boot, en µs.
+pub(super) fn spi_time_us() -> u64 {
+    // SAFETY: aucune précondition, lecture d'un compteur de esp_hal.c.
+    unsafe { hal_bmlite_spi_time_get() }
+}
+
+// ======================================================
+// Stockage NVS des métadonnées (feature `nvs`)
+// ======================================================
+
+/// Partition NVS partagée avec l'application (voir `SensorCtx::set_nvs_partition`).
+#[cfg(feature = "nvs")]
+pub(super) type NvsPartition = EspDefaultNvsPartition;
+
+#[cfg(feature = "nvs")]
+const NVS_NAMESPACE: &str = "fingerprint";
+#[cfg(feature = "nvs")]
+const NVS_KEY: &str = "meta";
+
+/// Blob de métadonnées dans l'espace de noms NVS du module.
+#[cfg(feature = "nvs")]
+pub(super) struct MetaStore(EspNvs<NvsDefault>);
+
+#[cfg(feature = "nvs")]
+impl MetaStore {
+    /// Sans `partition`, prend la partition par défaut (refusé si l'application l'a
+    /// déjà prise : elle doit alors la partager via `set_nvs_partition`).
+    pub(super) fn open(partition: Option<NvsPartition>) -> Result<Self> {
+        let partition = match partition {
+            Some(partition) => partition,
+            None => NvsPartition::take()?,
+        };
+        Ok(Self(EspNvs::new(partition, NVS_NAMESPACE, true)?))
+    }
+
+    pub(super) fn load(&mut self) -> Result<Option<Vec<u8>>> {
+        let Some(len) = self.0.blob_len(NVS_KEY)? else {
+            return Ok(None);
+        };
+        let mut buf = alloc::vec![0u8; len];
+        Ok(self.0.get_blob(NVS_KEY, &mut buf)?.map(<[u8]>::to_vec))
+    }
+
+    /// Écrit et valide (nvs_commit) le blob.
+    pub(super) fn save(&mut self, blob: &[u8]) -> Result<()> {
+        self.0.set_blob(NVS_KEY, blob)?;
+        Ok(())
+    }
+}
+
+// ======================================================
+// Trace des paquets HCP
+// ======================================================
+
+type WriteFn = unsafe extern "C" fn(u16, *const u8, u32) -> i32;
+type ReadFn = unsafe extern "C" fn(u16, *mut u8, u32) -> i32;
+
+// Callbacks C d'origine, le temps que les trampolines sont posés. Les callbacks ne
+// reçoivent pas la chaîne : statiques, comme côté C (une seule plateforme à la fois).
+static TRACED_WRITE: AtomicPtr<c_void> = AtomicPtr::new(ptr::null_mut());
+static TRACED_READ: AtomicPtr<c_void> = AtomicPtr::new(ptr::null_mut());
+
+// Octets en hexadécimal, tronqués à TRACE_MAX_BYTES
+struct Hex<'a>(&'a [u8]);
+
+impl fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for b in self.0.iter().take(TRACE_MAX_BYTES) {
+            write!(f, "{b:02x} ")?;
+        }
+        if self.0.len() > TRACE_MAX_BYTES {
+            write!(f, "... (+{})", self.0.len() - TRACE_MAX_BYTES)?;
+        }
+        Ok(())
+    }
+}
+
+// Journalise un transfert sans laisser un panic atteindre l'appelant C
+fn trace(dir: &str, data: &[u8], res: i32) {
+    let log = || fp_log!(trace, target: TRACE_TARGET, "HCP {dir} {} octets (res {res}): {}", data.len(), Hex(data));
+    #[cfg(feature = "std")]
+    let _ = std::panic::catch_unwind(log);
+    #[cfg(not(feature = "std"))]
+    log();
+}
+
+unsafe extern "C" fn trace_write(size: u16, data: *const u8, timeout: u32) -> i32 {
+    // SAFETY: TRACED_WRITE contient le callback C d'origine tant que ce trampoline
+    // est posé (set_packet_trace) ; `data` désigne `size` octets lisibles (contrat
+    // du callback `write`).
+    unsafe {
+        let write: WriteFn = core::mem::transmute(TRACED_WRITE.load(Ordering::Acquire));
+        let res = write(size, data, timeout);
+        trace("->", core::slice::from_raw_parts(data, size.into()), res);
+        res
+    }
+}
+
+unsafe extern "C" fn trace_read(size: u16, data: *mut u8, timeout: u32) -> i32 {
+    // SAFETY: idem avec TRACED_READ ; `data` désigne `size` octets remplis par le
+    // callback `read` (contenu indéfini mais initialisé s'il échoue : buffer du C).
+    unsafe {
+        let read: ReadFn = core::mem::transmute(TRACED_READ.load(Ordering::Acquire));
+        let res = read(size, data, timeout);
+        trace("<-", core::slice::from_raw_parts(data, size.into()), res);
+        res
+    }
+}
+
+// ======================================================
+// Commandes BM-Lite
+// ======================================================
+
+impl Chain {
+    // Champs que platform_init aurait pu corrompre : callbacks installés par le HAL et
+    // buffers d'alloc_config. Vérifié avant tout usage, pour échouer à l'init plutôt
+    // que sur un pointeur invalide plus tard.
+    fn validate(&self, buffers: &Buffers) -> core::result::Result<(), &'static str> {
+        // SAFETY: lecture seule d'une allocation valide (alloc_config).
+        let raw = unsafe { self.raw.as_ref() };
+        if raw.write.is_none() {
+            return Err("write");
+        }
+        if raw.read.is_none() {
+            return Err("read");
+        }
+        if raw.pkt_buffer != buffers.pkt || raw.pkt_buffer.is_null() {
+            return Err("pkt_buffer");
+        }
+        if raw.txrx_buffer != buffers.txrx || raw.txrx_buffer.is_null() {
+            return Err("txrx_buffer");
+        }
+        if raw.pkt_size_max != PKT_BUFFER_LEN as u32 {
+            return Err("pkt_size_max");
+        }
+        Ok(())
+    }
+
+    // Remet les buffers d'origine pour que deinit_board libère ce qui a été alloué
+    fn restore_buffers(&self, buffers: &Buffers) {
+        // SAFETY: écriture d'une allocation valide ; la plateforme n'exécute rien en
+        // parallèle (aucune commande HCP envoyée depuis platform_init).
+        unsafe {
+            let raw = &mut *self.raw.as_ptr();
+            raw.pkt_buffer = buffers.pkt;
+            raw.txrx_buffer = buffers.txrx;
+            raw.pkt_size_max = PKT_BUFFER_LEN as u32;
+        }
+    }
+
+    // Toutes les méthodes reposent sur les invariants du module (chaîne valide et
+    // initialisée, accès exclusif garanti par `&mut self`).
+    fn ptr(&self) -> *mut HCP_comm_t {
+        self.raw.as_ptr()
+    }
+
+    // Code de transport d'un échange, suivi par `link` pour repérer une contention de
+    // bus (erreurs de trame alors que le capteur répond encore)
+    fn check(&mut self, res: i32, what: &'static str) -> Result<()> {
+        match res {
+            BEP_RESULT_IO_ERROR => {
+                if self.link.record_error() {
+                    fp_warn!("BM-Lite: erreurs de trame répétées ({what}), contention SPI probable");
+                    return Err(FingerprintError::BusContention(what).into());
+                }
+            }
+            // Pas de réponse : ni preuve de vie ni erreur de trame
+            BEP_RESULT_TIMEOUT => {}
+            _ => self.link.record_ok(),
+        }
+        check_bep(res, what)
+    }
+
+    // Code renvoyé par le BM-Lite lui-même (ARG_RESULT), distinct du code de transport
+    fn bep_result(&self) -> i32 {
+        // SAFETY: lecture seule d'une allocation valide (invariants du module).
+        unsafe { self.raw.as_ref().bep_result }
+    }
+
+    // Vérifie le code de transport puis le code renvoyé par le capteur
+    fn check_both(&mut self, res: i32, what: &'static str) -> Result<()> {
+        self.check(res, what)?;
+        check_bep(self.bep_result(), what)
+    }
+
+    // Commande sans paramètre : codes transport et capteur vérifiés
+    fn send_cmd(&mut self, cmd: u32, arg: u32, what: &'static str) -> Result<()> {
+        // SAFETY: invariants du module.
+        let res = unsafe { bmlite_send_cmd(self.ptr(), cmd as u16, arg as u16) };
+        self.check_both(res, what)
+    }
+
+    // Octets de l'argument `arg` dans la dernière réponse (seul point de lecture de
+    // `chain.arg`). Le slice emprunte `self` : il ne survit pas à la commande suivante,
+    // qui réécrit pkt_buffer.
+    fn arg_bytes(&mut self, arg: u32, what: &'static str) -> Result<&[u8]> {
+        // SAFETY: invariants du module.
+        check_bep(unsafe { bmlite_get_arg(self.ptr(), arg as u16) }, what)?;
+        // SAFETY: bmlite_get_arg a fait pointer `arg.data` sur `arg.size` octets de pkt_buffer.
+        Ok(unsafe {
+            let a = &self.raw.as_ref().arg;
+            core::slice::from_raw_parts(a.data, a.size as usize)
+        })
+    }
+
+    // Argument `arg` de la dernière réponse, lu comme un entier little-endian (<= 4 octets)
+    fn arg_u32(&mut self, arg: u32, what: &'static str) -> Result<u32> {
+        let bytes = self.arg_bytes(arg, what)?;
+        let bytes = &bytes[..bytes.len().min(4)];
+        let mut le = [0u8; 4];
+        le[..bytes.len()].copy_from_slice(bytes);
+        Ok(u32::from_le_bytes(le))
+    }
+
+    /// Code renvoyé par le capteur dans la dernière réponse (ARG_RESULT).
+    pub fn last_result(&self) -> i32 {
+        self.bep_result()
+    }
+
+    /// Arguments connus de la dernière réponse HCP (absents -> `None`).
+    pub fn read_arg(&mut self) -> HcpArgView {
+        HcpArgView {
+            matched: self.arg_u32(fpc_hcp_arg_ARG_MATCH, "arg match").ok().map(|v| v != 0),
+            id: self.arg_u32(fpc_hcp_arg_ARG_ID, "arg id").ok().map(|v| v as u16),
+            count: self.arg_u32(fpc_hcp_arg_ARG_COUNT, "arg count").ok(),
+            score: None,
+            quality: None,
+            geometry: None,
+        }
+    }
+
+    /// Remet à zéro l'état HCP côté hôte (taille de paquet, argument courant, résultat,
+    /// buffers) et lit puis jette les réponses que le capteur tient encore prêtes.
+    pub fn reset_link(&mut self) {
+        let clear = |raw: &mut HCP_comm_t| {
+            raw.pkt_size = 0;
+            raw.arg = HCP_arg_t { size: 0, data: ptr::null_mut() };
+            raw.bep_result = 0;
+            // SAFETY: buffers de PKT_BUFFER_LEN / TXRX_BUFFER_LEN octets (alloc_config).
+            unsafe {
+                ptr::write_bytes(raw.pkt_buffer, 0, PKT_BUFFER_LEN);
+                ptr::write_bytes(raw.txrx_buffer, 0, TXRX_BUFFER_LEN);
+            }
+        };
+
+        // SAFETY: accès exclusif (&mut self) à une allocation valide (invariants du module).
+        clear(unsafe { self.raw.as_mut() });
+        for _ in 0..MAX_STALE_RESPONSES {
+            // SAFETY: lecture GPIO puis réception dans les buffers de la chaîne.
+            if !unsafe { hal_bmlite_get_status() } || unsafe { bmlite_receive(self.ptr()) } != 0 {
+                break;
+            }
+        }
+        // SAFETY: idem, la réception a pu remplir les buffers et `arg`.
+        clear(unsafe { self.raw.as_mut() });
+    }
+
+    /// Intercale (ou retire) les trampolines de trace autour des callbacks `write` /
+    /// `read` : chaque transfert est vidé en hexadécimal au niveau `trace`. Désactivée,
+    /// la chaîne retrouve les callbacks C d'origine, sans coût.
+    pub fn set_packet_trace(&mut self, enabled: bool) {
+        // SAFETY: accès exclusif (&mut self) à une allocation valide ; les callbacks
+        // sont posés (invariants du module) et aucune commande n'est en cours.
+        let raw = unsafe { self.raw.as_mut() };
+        match (enabled, self.traced, raw.write, raw.read) {
+            (true, false, Some(write), Some(read)) => {
+                TRACED_WRITE.store(write as *mut c_void, Ordering::Release);
+                TRACED_READ.store(read as *mut c_void, Ordering::Release);
+                raw.write = Some(trace_write);
+                raw.read = Some(trace_read);
+                self.traced = true;
+            }
+            (false, true, _, _) => {
+                // SAFETY: remplis avec les callbacks d'origine quand la trace a été posée.
+                unsafe {
+                    raw.write = Some(core::mem::transmute::<*mut c_void, WriteFn>(TRACED_WRITE.load(Ordering::Acquire)));
+                    raw.read = Some(core::mem::transmute::<*mut c_void, ReadFn>(TRACED_READ.load(Ordering::Acquire)));
+                }
+                self.traced = false;
+            }
+            _ => {}
+        }
+    }
+
+    pub fn info(&self) -> ChainInfo {
+        // SAFETY: lecture seule d'une allocation valide (invariants du module).
+        let c = unsafe { self.raw.as_ref() };
+        ChainInfo {
+            chain: self.raw.as_ptr().cast(),
+            comm_size: core::mem::size_of::<HCP_comm_t>(),
+            pkt_buffer: c.pkt_buffer,
+            txrx_buffer: c.txrx_buffer,
+            pkt_size_max: c.pkt_size_max,
+            has_write: c.write.is_some(),
+            has_read: c.read.is_some(),
+        }
+    }
+
+    /// Capture + identification. Retourne l'ID matché, `None` si aucun template ne correspond.
+    pub fn identify_finger(&mut self, timeout_ms: u32) -> Result<Option<u16>> {
+        let mut tid: u16 = 0;
+        let mut matched = false;
+        // SAFETY: invariants du module ; `tid` / `matched` sont des locaux valides.
+        self.check(
+            unsafe { bep_identify_finger(self.ptr(), timeout_ms, &mut tid, &mut matched) },
+            "bep_identify_finger",
+        )?;
+        // Le transport répond OK et le code capteur reste dans `bep_result` : seule une
+        // calibration absente est une erreur, les autres codes qualifient le non-match
+        // (voir `NoMatchReason`)
+        if self.bep_result() == BEP_RESULT_INVALID_CALIBRATION {
+            return Err(FingerprintError::NotCalibrated("bep_identify_finger").into());
+        }
+        Ok(matched.then_some(tid))
+    }
+
+    /// Capture d'une image (attend le doigt jusqu'à `timeout_ms`).
+    pub fn capture(&mut self, timeout_ms: u16) -> Result<()> {
+        // SAFETY: invariants du module.
+        let res = unsafe { bep_capture(self.ptr(), timeout_ms) };
+        self.check_both(res, "bep_capture")
+    }
+
+    #[cfg(feature = "enroll")]
+    pub fn enroll_start(&mut self) -> Result<()> {
+        self.send_cmd(fpc_hcp_cmd_CMD_ENROLL, fpc_hcp_arg_ARG_START, "enroll start")
+    }
+
+    /// Ajoute l'image capturée à l'enrôlement. Retourne le nombre d'échantillons restants.
+    #[cfg(feature = "enroll")]
+    pub fn enroll_add(&mut self) -> Result<u32> {
+        self.send_cmd(fpc_hcp_cmd_CMD_ENROLL, fpc_hcp_arg_ARG_ADD, "enroll add")?;
+        self.arg_u32(fpc_hcp_arg_ARG_COUNT, "enroll add count")
+    }
+
+    #[cfg(feature = "enroll")]
+    pub fn enroll_finish(&mut self) -> Result<()> {
+        self.send_cmd(fpc_hcp_cmd_CMD_ENROLL, fpc_hcp_arg_ARG_FINISH, "enroll finish")
+    }
+
+    /// Ferme un enrôlement laissé ouvert et jette le template en RAM. `Ok` si le
+    /// firmware a accepté la fermeture.
+    #[cfg(feature = "enroll")]
+    pub fn enroll_abort(&mut self) -> Result<()> {
+        let res = self.enroll_finish();
+
+        // SAFETY: invariants du module.
+        let _ = unsafe { bep_template_remove_ram(self.ptr()) };
+        res
+    }
+
+    #[cfg(feature = "enroll")]
+    pub fn sensor_calibrate(&mut self) -> Result<()> {
+        // SAFETY: invariants du module.
+        self.check(unsafe { bep_sensor_calibrate(self.ptr()) }, "bep_sensor_calibrate")
+    }
+
+    /// Reset logiciel (CMD_RESET) : le firmware redémarre, RAM BM-Lite vidée.
+    pub fn sw_reset(&mut self) -> Result<()> {
+        // SAFETY: invariants du module.
+        self.check(unsafe { bep_sw_reset(self.ptr()) }, "bep_sw_reset")
+    }
+
+    pub fn unique_id(&mut self) -> Result<[u8; UNIQUE_ID_LEN]> {
+        let mut id = [0u8; UNIQUE_ID_LEN];
+        // SAFETY: invariants du module ; bep_unique_id_get copie au plus 12 octets dans `id`.
+        self.check(unsafe { bep_unique_id_get(self.ptr(), id.as_mut_ptr()) }, "bep_unique_id_get")?;
+        Ok(id)
+    }
+
+    /// Version firmware (chaîne ASCII terminée par NUL côté BM-Lite).
+    pub fn firmware_version(&mut self) -> Result<String> {
+        let mut buf = [0u8; VERSION_LEN];
+        // SAFETY: invariants du module ; bep_version copie au plus `VERSION_LEN` octets dans `buf`.
+        self.check(
+            unsafe { bep_version(self.ptr(), buf.as_mut_ptr().cast(), VERSION_LEN as i32) },
+            "bep_version",
+        )?;
+        let len = buf.iter().position(|&b| b == 0).unwrap_or(VERSION_LEN);
+        Ok(String::from_utf8_lossy(&buf[..len]).into_owned())
+    }
+
+    pub fn template_count(&mut self) -> Result<u16> {
+        let mut count: u16 = 0;
+        // SAFETY: invariants du module ; `count` est un local valide.
+        self.check(
+            unsafe { bep_template_get_count(self.ptr(), &mut count) },
+            "bep_template_get_count",
+        )?;
+        Ok(count)
+    }
+
+    /// IDs occupés. La liste est copiée : `arg.data` pointe dans `pkt_buffer`,
+    /// écrasé par la commande suivante.
+    pub fn template_ids(&mut self) -> Result<Vec<u16>> {
+        if self.template_count()? == 0 {
+            return Ok(Vec::new());
+        }
+
+        // SAFETY: invariants du module.
+        self.check(unsafe { bep_template_get_ids(self.ptr()) }, "bep_template_get_ids")?;
+
+        // SAFETY: après bep_template_get_ids, `arg` désigne `arg.size` octets dans
+        // `pkt_buffer` (tableau de u16), valides jusqu'à la prochaine commande.
+        let bytes = unsafe {
+            let arg = &(*self.ptr()).arg;
+            if arg.data.is_null() {
+                return Ok(Vec::new());
+            }
+            core::slice::from_raw_parts(arg.data, arg.size as usize)
+        };
+        Ok(bytes
+            .chunks_exact(2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+            .collect())
+    }
+
+    /// Copie le template `id` (flash -> RAM BM-Lite -> hôte). Le template en RAM
+    /// est supprimé ensuite pour ne pas interférer avec un enrôlement.
+    pub fn template_export(&mut self, id: u16) -> Result<Vec<u8>> {
+        // Le template tient dans pkt_buffer : on réserve sa taille
+        // SAFETY: lecture seule d'une allocation valide (invariants du module).
+        let max = unsafe { self.raw.as_ref().pkt_size_max };
+        let mut data = alloc::vec![0u8; max as usize];
+        let len = self.template_export_into(id, &mut data)?;
+        data.truncate(len);
+        Ok(data)
+    }
+
+    /// Comme `template_export`, dans `buf` ; renvoie la taille du template. Un buffer
+    /// trop petit est une erreur (le firmware tronquerait la copie sans le signaler).
+    pub fn template_export_into(&mut self, id: u16, buf: &mut [u8]) -> Result<usize> {
+        // SAFETY: invariants du module.
+        let res = unsafe { bep_template_load_storage(self.ptr(), id) };
+        self.check_both(res, "bep_template_load_storage")?;
+
+        // bmlite_copy_arg tronque la taille à 16 bits ; arg.size donne la taille réelle
+        let size = buf.len().min(usize::from(u16::MAX)) as u32;
+        // SAFETY: invariants du module ; `buf` fait au moins `size` octets et bep_template_get en copie au plus autant.
+        let res = unsafe { bep_template_get(self.ptr(), buf.as_mut_ptr(), size) };
+        let got = self.check(res, "bep_template_get").map(|()| {
+            // SAFETY: lecture seule d'une allocation valide (invariants du module).
+            unsafe { self.raw.as_ref().arg.size }
+        });
+
+        // SAFETY: invariants du module.
+        let _ = unsafe { bep_template_remove_ram(self.ptr()) };
+
+        let got = got? as usize;
+        if got > size as usize {
+            return Err(anyhow!("export buffer too small: template is {got} bytes, buffer {}", buf.len()));
+        }
+        Ok(got)
+    }
+
+    /// Charge `data` en RAM (format exporté par `template_export`) puis le sauvegarde
+    /// en flash sous `id`.
+    #[cfg(feature = "enroll")]
+    pub fn template_import(&mut self, id: u16, data: &[u8]) -> Result<()> {
+        let res = self.template_put(data).and_then(|()| self.template_save(id));
+
+        // SAFETY: invariants du module.
+        let _ = unsafe { bep_template_remove_ram(self.ptr()) };
+        res
+    }
+
+    // Charge `data` dans la RAM BM-Lite, sans l'écrire en flash
+    fn template_put(&mut self, data: &[u8]) -> Result<()> {
+        let len = u16::try_from(data.len()).map_err(|_| FingerprintError::Bep {
+            what: "bep_template_put",
+            code: BEP_RESULT_INVALID_ARGUMENT,
+        })?;
+
+        // SAFETY: invariants du module ; `data` n'est que lu (copié dans pkt_buffer
+        // par bmlite_send_cmd_arg) malgré le `uint8_t *` non-const côté C.
+        let res = unsafe { bep_template_put(self.ptr(), data.as_ptr().cast_mut(), len) };
+        self.check_both(res, "bep_template_put")
+    }
+
+    /// Compare le doigt posé au seul template `data`, chargé en RAM le temps de
+    /// l'opération (CMD_MATCH) : la flash n'est jamais écrite. Le template en RAM
+    /// est supprimé à la fin, y compris sur erreur.
+    pub fn verify_transient(&mut self, data: &[u8], timeout_ms: u16) -> Result<bool> {
+        let res = self.template_put(data).and_then(|()| {
+            self.capture(timeout_ms)?;
+            self.send_cmd(fpc_hcp_cmd_CMD_IMAGE, fpc_hcp_arg_ARG_EXTRACT, "image extract")?;
+            self.send_cmd(fpc_hcp_cmd_CMD_MATCH, fpc_hcp_arg_ARG_NONE, "match")?;
+            Ok(self.arg_u32(fpc_hcp_arg_ARG_MATCH, "match result")? != 0)
+        });
+
+        // SAFETY: invariants du module.
+        let _ = unsafe { bep_template_remove_ram(self.ptr()) };
+        res
+    }
+
+    /// Capture une image et lit le template qui en est extrait (CMD_IMAGE/ARG_EXTRACT
+    /// puis CMD_TEMPLATE/ARG_DATA), sans rien écrire en flash. Le template en RAM est
+    /// supprimé ensuite, y compris sur erreur.
+    pub fn probe_export(&mut self, timeout_ms: u16) -> Result<Vec<u8>> {
+        // SAFETY: lecture seule d'une allocation valide (invariants du module).
+        let max = unsafe { self.raw.as_ref().pkt_size_max };
+        let mut data = alloc::vec![0u8; max as usize];
+        let size = data.len().min(usize::from(u16::MAX)) as u32;
+        let res = self.capture(timeout_ms).and_then(|()| {
+            self.send_cmd(fpc_hcp_cmd_CMD_IMAGE, fpc_hcp_arg_ARG_EXTRACT, "image extract")?;
+            // SAFETY: invariants du module ; `data` fait au moins `size` octets et bep_template_get en copie au plus autant.
+            let res = unsafe { bep_template_get(self.ptr(), data.as_mut_ptr(), size) };
+            self.check(res, "bep_template_get")?;
+            // SAFETY: lecture seule d'une allocation valide (invariants du module).
+            Ok(unsafe { self.raw.as_ref().arg.size } as usize)
+        });
+
+        // SAFETY: invariants du module.
+        let _ = unsafe { bep_template_remove_ram(self.ptr()) };
+
+        let got = res?;
+        if got > size as usize {
+            return Err(anyhow!("probe template too large: {got} bytes, buffer {size}"));
+        }
+        data.truncate(got);
+        Ok(data)
+    }
+
+    #[cfg(feature = "enroll")]
+    pub fn template_save(&mut self, id: u16) -> Result<()> {
+        // SAFETY: invariants du module.
+        self.check(unsafe { bep_template_save(self.ptr(), id) }, "bep_template_save")
+    }
+
+    pub fn template_remove(&mut self, id: u16) -> Result<()> {
+        // SAFETY: invariants du module.
+        self.check(unsafe { bep_template_remove(self.ptr(), id) }, "bep_template_remove")
+    }
+
+    pub fn template_remove_all(&mut self) -> Result<()> {
+        // SAFETY: invariants du module.
+        self.check(unsafe { bep_template_remove_all(self.ptr()) }, "bep_template_remove_all")
+    }
+
+    pub fn wait_finger_present(&mut self, timeout_ms: u16) -> Result<()> {
+        // SAFETY: invariants du module.
+        let res = unsafe { sensor_wait_finger_present(self.ptr(), timeout_ms) };
+        self.check_both(res, "sensor_wait_finger_present")
+    }
+
+    pub fn wait_finger_not_present(&mut self, timeout_ms: u16) -> Result<()> {
+        // SAFETY: invariants du module.
+        let res = unsafe { sensor_wait_finger_not_present(self.ptr(), timeout_ms) };
+        self.check_both(res, "sensor_wait_finger_not_present")
+    }
+}
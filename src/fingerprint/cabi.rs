@@ -0,0 +1,142 @@
+// ABI C des opérations principales, pour appeler le module depuis un firmware C/C++
+// (feature `cabi`, qui active `std` : passe par le singleton global).
+//
+// Prototypes côté C :
+//
+//     int32_t fp_init(void);
+//     int32_t fp_deinit(void);
+//     int32_t fp_enroll(uint16_t *out_id);                      // feature `enroll`
+//     int32_t fp_check(uint32_t timeout_ms, bool *out_matched);
+//     int32_t fp_wipe(void);
+//
+// Chaque fonction renvoie un code compatible `fpc_bep_result_t` : 0 en cas de succès,
+// le code BM-Lite d'origine quand il existe, sinon le code le plus proche (voir
+// `result_code`). Un panic est rattrapé et renvoyé comme FPC_BEP_RESULT_INTERNAL_ERROR
+// plutôt que de dérouler à travers le C (si le build déroule les panics) ; le Mutex
+// global reste alors empoisonné et les appels suivants échouent de même.
+//
+// Threading : tous les appels passent par le Mutex global du module et sont donc
+// sérialisés ; ils sont bloquants (`fp_check` jusqu'à `timeout_ms`) et ne doivent
+// pas être appelés depuis une ISR. Les pointeurs de sortie peuvent être NULL.
+//
+// Pas de `forbid(unsafe_code)` : `#[no_mangle]` et l'écriture des pointeurs de sortie
+// en relèvent.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use anyhow::Result;
+
+use super::error::{
+    FingerprintError, BEP_RESULT_GENERAL_ERROR, BEP_RESULT_INVALID_CALIBRATION,
+    BEP_RESULT_IO_ERROR, BEP_RESULT_NOT_SUPPORTED, BEP_RESULT_TIMEOUT,
+};
+
+// Codes fpc_bep_result_t propres à l'ABI C (fpc_bep_types.h)
+const BEP_RESULT_OK: i32 = 0;
+const BEP_RESULT_INTERNAL_ERROR: i32 = -2;
+const BEP_RESULT_CANCELLED: i32 = -5;
+const BEP_RESULT_NO_RESOURCE: i32 = -7;
+const BEP_RESULT_WRONG_STATE: i32 = -10;
+const BEP_RESULT_ID_NOT_UNIQUE: i32 = -12;
+const BEP_RESULT_INVALID_FORMAT: i32 = -14;
+const BEP_RESULT_TOO_MANY_BAD_IMAGES: i32 = -22;
+const BEP_RESULT_CRYPTO_ERROR: i32 = -23;
+const BEP_RESULT_NOT_INITIALIZED: i32 = -26;
+
+// Code `fpc_bep_result_t` équivalent à une erreur du module
+fn result_code(err: &anyhow::Error) -> i32 {
+    match FingerprintError::of(err) {
+        Some(FingerprintError::Bep { code, .. }) => code,
+        Some(FingerprintError::NotInitialized) => BEP_RESULT_NOT_INITIALIZED,
+        Some(FingerprintError::Timeout(_)) => BEP_RESULT_TIMEOUT,
+        Some(FingerprintError::NotCalibrated(_)) => BEP_RESULT_INVALID_CALIBRATION,
+        Some(FingerprintError::Unsupported(_)) => BEP_RESULT_NOT_SUPPORTED,
+        Some(FingerprintError::BusContention(_) | FingerprintError::EnrollInterrupted { .. }) => {
+            BEP_RESULT_IO_ERROR
+        }
+        Some(FingerprintError::LowQuality { .. }) => BEP_RESULT_TOO_MANY_BAD_IMAGES,
+        Some(FingerprintError::Cancelled) => BEP_RESULT_CANCELLED,
+        Some(FingerprintError::Integrity) => BEP_RESULT_CRYPTO_ERROR,
+        Some(
+            FingerprintError::UnsupportedBackup(_) | FingerprintError::IncompatibleTemplate(_),
+        ) => BEP_RESULT_INVALID_FORMAT,
+        Some(FingerprintError::StoreFull) => BEP_RESULT_NO_RESOURCE,
+        Some(FingerprintError::AlreadyEnrolled) => BEP_RESULT_ID_NOT_UNIQUE,
+        Some(FingerprintError::InvalidChain(_)) => BEP_RESULT_INTERNAL_ERROR,
+        Some(FingerprintError::Locked(_) | FingerprintError::LockedOut { .. }) => {
+            BEP_RESULT_WRONG_STATE
+        }
+        Some(
+            FingerprintError::UpdateFailed { .. } | FingerprintError::VerificationFailed { .. },
+        )
+        | None => BEP_RESULT_GENERAL_ERROR,
+    }
+}
+
+// Exécute `op` sans laisser un panic traverser la frontière C
+fn call<T>(op: impl FnOnce() -> Result<T>, out: impl FnOnce(T)) -> i32 {
+    match catch_unwind(AssertUnwindSafe(op)) {
+        Ok(Ok(value)) => {
+            out(value);
+            BEP_RESULT_OK
+        }
+        Ok(Err(e)) => {
+            fp_error!("cabi: {e}");
+            result_code(&e)
+        }
+        Err(_) => BEP_RESULT_INTERNAL_ERROR,
+    }
+}
+
+/// Voir `fingerprint::init`.
+#[no_mangle]
+pub extern "C" fn fp_init() -> i32 {
+    call(super::init, |_| ())
+}
+
+/// Voir `fingerprint::deinit`.
+#[no_mangle]
+pub extern "C" fn fp_deinit() -> i32 {
+    call(super::deinit, |()| ())
+}
+
+/// Enrôle un doigt (voir `fingerprint::enroll_user`) et écrit son ID dans `out_id`.
+///
+/// # Safety
+///
+/// `out_id` est NULL ou pointe vers un `uint16_t` accessible en écriture.
+#[cfg(feature = "enroll")]
+#[no_mangle]
+pub unsafe extern "C" fn fp_enroll(out_id: *mut u16) -> i32 {
+    call(super::enroll_user, |report| {
+        // SAFETY: garanti par l'appelant (voir ci-dessus), NULL ignoré.
+        if let Some(out) = unsafe { out_id.as_mut() } {
+            *out = report.template_id;
+        }
+    })
+}
+
+/// Identifie un doigt (voir `fingerprint::check_once`) ; `out_matched` reçoit le
+/// résultat. Aucun doigt avant `timeout_ms` : FPC_BEP_RESULT_TIMEOUT.
+///
+/// # Safety
+///
+/// `out_matched` est NULL ou pointe vers un `bool` accessible en écriture.
+#[no_mangle]
+pub unsafe extern "C" fn fp_check(timeout_ms: u32, out_matched: *mut bool) -> i32 {
+    call(
+        || super::check_once(timeout_ms),
+        |matched| {
+            // SAFETY: garanti par l'appelant (voir ci-dessus), NULL ignoré.
+            if let Some(out) = unsafe { out_matched.as_mut() } {
+                *out = matched;
+            }
+        },
+    )
+}
+
+/// Supprime tous les templates non épinglés (voir `fingerprint::wipe_templates`).
+#[no_mangle]
+pub extern "C" fn fp_wipe() -> i32 {
+    call(|| super::wipe_templates(false), |_| ())
+}
@@ -0,0 +1,108 @@
+#![forbid(unsafe_code)]
+
+// Cache hôte des IDs enrôlés : évite un aller-retour SPI (bep_template_get_count /
+// get_ids) à chaque `is_user_enrolled`, `free_slots`, ...
+//
+// Rempli à l'init puis tenu à jour par les opérations de ce module (enrôlement,
+// suppression, effacement, import). Il devient faux si les templates sont modifiés
+// hors de ce contexte : autre hôte sur le même capteur, effacement par l'outil FPC,
+// écriture qui échoue à mi-chemin. `refresh_cache` resynchronise depuis le capteur.
+
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use anyhow::Result;
+
+use super::error::{FingerprintError, BEP_RESULT_IO_ERROR};
+use super::sensor::{not_initialized, SensorCtx};
+
+/// `None` = contenu inconnu (avant init, après un échec) : la prochaine lecture
+/// interroge le capteur.
+#[derive(Default)]
+pub(super) struct EnrolledCache(Option<BTreeSet<u16>>);
+
+impl EnrolledCache {
+    pub(super) const fn new() -> Self {
+        Self(None)
+    }
+
+    pub(super) fn set(&mut self, ids: &[u16]) {
+        self.0 = Some(ids.iter().copied().collect());
+    }
+
+    pub(super) fn insert(&mut self, id: u16) {
+        if let Some(ids) = self.0.as_mut() {
+            ids.insert(id);
+        }
+    }
+
+    pub(super) fn remove(&mut self, id: u16) {
+        if let Some(ids) = self.0.as_mut() {
+            ids.remove(&id);
+        }
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.0 = Some(BTreeSet::new());
+    }
+
+    pub(super) fn invalidate(&mut self) {
+        self.0 = None;
+    }
+
+    // `None` si le contenu est inconnu
+    pub(super) fn contains(&self, id: u16) -> Option<bool> {
+        self.0.as_ref().map(|ids| ids.contains(&id))
+    }
+
+    // IDs triés, `None` si le contenu est inconnu
+    pub(super) fn ids(&self) -> Option<impl Iterator<Item = u16> + '_> {
+        self.0.as_ref().map(|ids| ids.iter().copied())
+    }
+
+    pub(super) fn count(&self) -> Option<u16> {
+        self.0.as_ref().map(|ids| ids.len() as u16)
+    }
+}
+
+impl SensorCtx {
+    /// Relit les IDs enrôlés sur le capteur et remplace le cache.
+    ///
+    /// À appeler si les templates ont pu être modifiés hors de ce contexte (autre
+    /// hôte, outil FPC) ou après une erreur en cours d'écriture.
+    pub fn refresh_cache(&mut self) -> Result<Vec<u16>> {
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+        let ids = chain.template_ids()?;
+        self.enrolled.set(&ids);
+        Ok(ids)
+    }
+
+    // Un match sur un ID absent des templates (réponse corrompue, firmware en vrac)
+    // ne doit pas ouvrir : le cache est d'abord relu (template enrôlé par un autre
+    // hôte), puis l'ID encore inconnu est traité comme une trame corrompue, que
+    // `identify_retry` retente.
+    pub(super) fn check_matched_id(&mut self, id: u16) -> Result<()> {
+        if self.enrolled.contains(id) == Some(true) {
+            return Ok(());
+        }
+        if self.refresh_cache()?.contains(&id) {
+            return Ok(());
+        }
+        fp_warn!(target: self.log_target, "BM-Lite: match sur l'ID {id} inconnu, réponse ignorée");
+        Err(FingerprintError::Bep {
+            what: "identify",
+            code: BEP_RESULT_IO_ERROR,
+        }
+        .into())
+    }
+
+    /// IDs enrôlés, triés, depuis le cache (relus sur le capteur s'il est inconnu).
+    pub fn enrolled_ids(&mut self) -> Result<Vec<u16>> {
+        if self.chain.is_none() {
+            return Err(not_initialized());
+        }
+        match &self.enrolled.0 {
+            Some(ids) => Ok(ids.iter().copied().collect()),
+            None => self.refresh_cache(),
+        }
+    }
+}
@@ -0,0 +1,30 @@
+#![forbid(unsafe_code)]
+
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Jeton d'annulation partagé entre la tâche qui attend le capteur et celle qui
+/// veut l'interrompre. Les clones partagent le même état.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Demande l'arrêt de l'attente en cours (effet au plus tard après une tranche
+    /// d'attente firmware, voir `SensorCtx::wait_for_finger_cancellable`).
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+
+    /// Réarme le jeton pour une nouvelle attente.
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
@@ -0,0 +1,161 @@
+#![forbid(unsafe_code)]
+
+// Source de temps du contexte : tout ce qui mesure ou attend côté hôte (verrouillage
+// après non-matchs, pauses et budget de `IdentifyPolicy`, fenêtres de `verify_group`,
+// écritures NVS périodiques, dates des statistiques et des matchs signés) passe par
+// le `Clock` du contexte plutôt que par l'horloge système.
+//
+// `MockClock` (feature `std`) rend ces comportements testables sans matériel ni
+// attente réelle : le temps n'avance que sur `advance` et sur les pauses demandées,
+// rendues instantanées. Les délais tenus par le firmware (attente du doigt, timeout
+// d'une capture) restent en temps réel, côté capteur.
+
+use alloc::boxed::Box;
+#[cfg(feature = "std")]
+use alloc::sync::Arc;
+use anyhow::Result;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+use super::bep;
+use super::sensor::SensorCtx;
+
+/// Horloge injectée dans le contexte (voir `SensorCtx::set_clock`).
+pub trait Clock: Send {
+    /// Temps monotone en ms, d'origine quelconque (boot, création de l'horloge).
+    fn now_ms(&self) -> u64;
+
+    /// Heure en secondes Unix, `None` tant qu'elle n'est pas réglée (SNTP, RTC).
+    fn unix_time_s(&self) -> Option<u64>;
+
+    /// Pause bloquante de `ms` millisecondes.
+    fn sleep_ms(&self, ms: u32);
+}
+
+/// Horloge réelle (défaut) : esp_timer et vTaskDelay sur la cible, horloge de l'hôte
+/// avec `sim` ; heure système avec la feature `std`, jamais réglée sans.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> u64 {
+        bep::tick_ms()
+    }
+
+    #[cfg(feature = "std")]
+    fn unix_time_s(&self) -> Option<u64> {
+        // En deçà (avant 2020), l'horloge système n'a pas été mise à l'heure
+        const MIN_WALL_CLOCK_S: u64 = 1_577_836_800;
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()?
+            .as_secs();
+        (now >= MIN_WALL_CLOCK_S).then_some(now)
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn unix_time_s(&self) -> Option<u64> {
+        None
+    }
+
+    fn sleep_ms(&self, ms: u32) {
+        bep::busy_wait(ms);
+    }
+}
+
+/// Horloge simulée pour les tests : part de 0 ms, heure Unix non réglée. Les clones
+/// partagent le même temps : garder un clone pour avancer celui du contexte.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default)]
+pub struct MockClock(Arc<Mutex<MockTime>>);
+
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+struct MockTime {
+    now_ms: u64,
+    // Heure Unix réglée (s) et `now_ms` au moment du réglage
+    unix_origin: Option<(u64, u64)>,
+}
+
+#[cfg(feature = "std")]
+impl MockClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Avance le temps de `ms` (l'heure Unix suit, si elle est réglée).
+    pub fn advance(&self, ms: u64) {
+        self.0.lock().unwrap().now_ms += ms;
+    }
+
+    /// Règle l'heure Unix à l'instant présent (`None` = pas encore mise à l'heure).
+    pub fn set_unix_time(&self, unix_s: Option<u64>) {
+        let mut time = self.0.lock().unwrap();
+        time.unix_origin = unix_s.map(|s| (s, time.now_ms));
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for MockClock {
+    fn now_ms(&self) -> u64 {
+        self.0.lock().unwrap().now_ms
+    }
+
+    fn unix_time_s(&self) -> Option<u64> {
+        let time = self.0.lock().unwrap();
+        let (origin_s, set_at_ms) = time.unix_origin?;
+        Some(origin_s + (time.now_ms - set_at_ms) / 1000)
+    }
+
+    // Pas d'attente réelle : la pause fait seulement avancer le temps
+    fn sleep_ms(&self, ms: u32) {
+        self.advance(ms.into());
+    }
+}
+
+/// `None` = `SystemClock`, pour un `SensorCtx::new` constant.
+#[derive(Default)]
+pub(super) struct ClockSource(Option<Box<dyn Clock>>);
+
+impl ClockSource {
+    pub(super) const fn new() -> Self {
+        Self(None)
+    }
+
+    fn get(&self) -> &dyn Clock {
+        self.0.as_deref().unwrap_or(&SystemClock)
+    }
+
+    pub(super) fn now_ms(&self) -> u64 {
+        self.get().now_ms()
+    }
+
+    pub(super) fn unix_time_s(&self) -> Option<u64> {
+        self.get().unix_time_s()
+    }
+
+    pub(super) fn sleep_ms(&self, ms: u32) {
+        self.get().sleep_ms(ms);
+    }
+}
+
+impl SensorCtx {
+    /// Remplace l'horloge du contexte (défaut : `SystemClock`), par exemple par un
+    /// `MockClock` pour tester verrouillage, pauses et budgets sans attendre. Refusé
+    /// si la configuration est verrouillée : l'horloge décide de la fin d'un
+    /// verrouillage. Les échéances en cours sont reportées sur la nouvelle horloge :
+    /// un verrouillage garde sa durée restante, la série de non-matchs repart de zéro.
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) -> Result<()> {
+        self.check_unlocked("set_clock")?;
+        let old_now = self.clock.now_ms();
+        self.clock = ClockSource(Some(Box::new(clock)));
+        let new_now = self.clock.now_ms();
+        self.lockout.rebase(old_now, new_now);
+        #[cfg(feature = "nvs")]
+        {
+            self.nvs_flushed_ms = new_now;
+        }
+        Ok(())
+    }
+}
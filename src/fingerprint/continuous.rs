@@ -0,0 +1,149 @@
+#![forbid(unsafe_code)]
+
+// Identification continue (feature `std`) : une tâche de fond identifie chaque doigt
+// posé et appelle le callback de l'application.
+//
+// Le firmware BM-Lite n'a pas de mode "scan autonome" (pas de commande HCP pour
+// identifier seul et lever l'IRQ sur un match) : on retombe donc toujours sur la
+// scrutation côté hôte. L'attente du doigt est découpée en tranches de
+// `SCAN_SLICE_MS`, verrou relâché entre deux tranches, pour que le reste de l'API
+// (snapshot, enrôlement, ...) reste utilisable pendant le scan.
+//
+// Aucune routine d'interruption GPIO n'est installée (ligne IRQ scrutée) : la
+// "détection IRQ" de fond est cette tâche. Les opérations exclusives (enrôlement,
+// calibration, export / import) la masquent (`mask_irq`) le temps de l'opération,
+// masque posé avant d'attendre le verrou : le doigt posé pour un enrôlement ne
+// déclenche pas une identification du scan qui tiendrait le verrou à ce moment-là.
+
+use anyhow::Result;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use super::global::with_sensor;
+use super::{snapshot, CancelToken, FingerprintError};
+
+// Tranche d'attente du doigt, verrou tenu
+const SCAN_SLICE_MS: u32 = 200;
+// Timeout de l'identification une fois le doigt détecté
+const IDENTIFY_TIMEOUT_MS: u32 = 2_000;
+// Pause après une erreur capteur, pour ne pas boucler à pleine vitesse
+const ERROR_PAUSE_MS: u64 = 1_000;
+const SCAN_STACK_SIZE: usize = 8 * 1024;
+
+static SCAN: Mutex<Option<(CancelToken, JoinHandle<()>)>> = Mutex::new(None);
+
+// Masques posés (manuels et opérations exclusives en cours), chacun levé par son
+// propre `unmask_irq`
+static IRQ_MASK: AtomicU32 = AtomicU32::new(0);
+
+/// Masque la détection de fond (`start_continuous`) : tant qu'un masque est posé, la
+/// tâche de scan n'attend plus de doigt et n'identifie plus, sans être arrêtée. Les
+/// masques s'empilent : chaque `mask_irq` doit être suivi d'un `unmask_irq`.
+///
+/// Automatique pendant les opérations exclusives de l'API libre ; à appeler
+/// soi-même pour enchaîner plusieurs opérations sans scan intercalé.
+pub fn mask_irq() {
+    IRQ_MASK.fetch_add(1, Ordering::AcqRel);
+}
+
+/// Lève un masque posé par `mask_irq`. Sans effet si aucun masque n'est posé.
+pub fn unmask_irq() {
+    let _ = IRQ_MASK.fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| n.checked_sub(1));
+}
+
+/// Vrai si au moins un masque `mask_irq` est posé.
+pub fn is_irq_masked() -> bool {
+    IRQ_MASK.load(Ordering::Acquire) != 0
+}
+
+// Masque tenu par une opération exclusive, levé même si elle panique
+pub(super) struct IrqMaskGuard;
+
+impl IrqMaskGuard {
+    pub(super) fn new() -> Self {
+        mask_irq();
+        Self
+    }
+}
+
+impl Drop for IrqMaskGuard {
+    fn drop(&mut self) {
+        unmask_irq();
+    }
+}
+
+/// Démarre l'identification continue : `callback` reçoit l'ID de chaque match.
+///
+/// Le callback est appelé depuis la tâche de scan, hors du verrou capteur : il peut
+/// rappeler l'API `fingerprint`, sauf `stop_continuous` (la tâche s'attendrait
+/// elle-même). Un scan déjà actif est arrêté et remplacé.
+pub fn start_continuous(callback: impl FnMut(u16) + Send + 'static) -> Result<()> {
+    if !snapshot().initialized {
+        return Err(FingerprintError::NotInitialized.into());
+    }
+    stop_continuous();
+
+    let cancel = CancelToken::new();
+    let token = cancel.clone();
+    let handle = thread::Builder::new()
+        .name("fp-scan".into())
+        .stack_size(SCAN_STACK_SIZE)
+        .spawn(move || scan_loop(&token, callback))?;
+
+    fp_info!(target: log_target(), "Scan continu démarré (scrutation, pas de mode autonome firmware)");
+    *SCAN.lock().unwrap() = Some((cancel, handle));
+    Ok(())
+}
+
+/// Arrête l'identification continue et attend la fin de la tâche (au plus une
+/// identification en cours). Sans effet si aucun scan n'est actif.
+pub fn stop_continuous() {
+    let Some((cancel, handle)) = SCAN.lock().unwrap().take() else {
+        return;
+    };
+    cancel.cancel();
+    let _ = handle.join();
+    fp_info!(target: log_target(), "Scan continu arrêté");
+}
+
+fn log_target() -> &'static str {
+    with_sensor(|ctx| ctx.log_target)
+}
+
+fn scan_loop(cancel: &CancelToken, mut callback: impl FnMut(u16)) {
+    while !cancel.is_cancelled() {
+        // Désarmé : l'identification répondrait tout de suite, inutile de tenir le verrou
+        // Masqué : une opération exclusive attend le verrou ou le tient
+        if !snapshot().armed || is_irq_masked() {
+            thread::sleep(Duration::from_millis(SCAN_SLICE_MS.into()));
+            continue;
+        }
+
+        let res = with_sensor(|ctx| {
+            if is_irq_masked() || !ctx.wait_for_finger(SCAN_SLICE_MS)? {
+                return Ok(None);
+            }
+            // Masque posé pendant l'attente : le doigt est pour l'opération exclusive
+            if is_irq_masked() {
+                return Ok(None);
+            }
+            let best = ctx.identify_candidates(1, IDENTIFY_TIMEOUT_MS)?;
+            Ok(best.first().map(|&(tid, _)| tid))
+        });
+
+        match res {
+            Ok(Some(tid)) => callback(tid),
+            Ok(None) => {}
+            Err(e) => {
+                if let Some(FingerprintError::NotInitialized) = FingerprintError::of(&e) {
+                    fp_warn!(target: log_target(), "Scan continu interrompu : capteur non initialisé");
+                    return;
+                }
+                fp_warn!(target: log_target(), "Scan continu : {e}");
+                thread::sleep(Duration::from_millis(ERROR_PAUSE_MS));
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+#![forbid(unsafe_code)]
+
+// Export / import chiffré des templates (feature `crypto`).
+//
+// Format : nonce (12 octets) || texte chiffré || tag Poly1305 (16 octets).
+// ChaCha20-Poly1305 plutôt qu'AES-GCM : pas besoin de l'accélérateur AES, et
+// rapide en logiciel sur Xtensa.
+
+use alloc::vec::Vec;
+use anyhow::Result;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+
+use super::bep;
+use super::error::FingerprintError;
+use super::integrity::validate_template_bytes;
+use super::sensor::{not_initialized, SensorCtx};
+
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+// Données associées : lie le blob à ce format (change si le format évolue)
+const AAD: &[u8] = b"fingerprint-v3/template/v1";
+
+fn cipher(key: &[u8; 32]) -> ChaCha20Poly1305 {
+    ChaCha20Poly1305::new(Key::from_slice(key))
+}
+
+impl SensorCtx {
+    /// Exporte le template `id` chiffré et authentifié avec `key`.
+    ///
+    /// Le nonce est tiré du RNG matériel : chiffrer deux fois le même template
+    /// donne deux blobs différents.
+    pub fn export_template_encrypted(&mut self, id: u16, key: &[u8; 32]) -> Result<Vec<u8>> {
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+        let data = chain.template_export(id)?;
+
+        let mut nonce = [0u8; NONCE_LEN];
+        bep::fill_random(&mut nonce);
+
+        let sealed = cipher(key)
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &data,
+                    aad: AAD,
+                },
+            )
+            .map_err(|_| FingerprintError::Integrity)?;
+
+        let mut out = Vec::with_capacity(NONCE_LEN + sealed.len());
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&sealed);
+        Ok(out)
+    }
+
+    /// Déchiffre `blob` (produit par `export_template_encrypted`) et le sauvegarde
+    /// sous `id`. Retourne `FingerprintError::Integrity` si le blob a été modifié
+    /// ou si la clé est incorrecte, `IncompatibleTemplate` si le contenu déchiffré ne
+    /// passe pas `validate_template_bytes` ; rien n'est alors écrit sur le capteur.
+    #[cfg(feature = "enroll")]
+    pub fn import_template_encrypted(
+        &mut self,
+        id: u16,
+        blob: &[u8],
+        key: &[u8; 32],
+    ) -> Result<()> {
+        self.check_unlocked("import_template_encrypted")?;
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+
+        if blob.len() < NONCE_LEN + TAG_LEN {
+            return Err(FingerprintError::Integrity.into());
+        }
+        let (nonce, sealed) = blob.split_at(NONCE_LEN);
+        let data = cipher(key)
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: sealed,
+                    aad: AAD,
+                },
+            )
+            .map_err(|_| FingerprintError::Integrity)?;
+        validate_template_bytes(&data)?;
+
+        chain.template_import(id, &data)?;
+        self.enrolled.insert(id);
+        self.mirror.store(id, &data);
+        self.qualities.remove(id);
+        self.checksums.remove(id);
+        self.stats.remove(id);
+        fp_info!(target: self.log_target, "Template {id} importé ({} octets)", data.len());
+        Ok(())
+    }
+}
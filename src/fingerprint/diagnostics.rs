@@ -0,0 +1,117 @@
+#![forbid(unsafe_code)]
+
+// Rapport de diagnostic pour le support : tout ce que les requêtes séparées
+// (version, identifiant, santé, statistiques, qualité du lien, ...) donnent, en un
+// seul appel et une seule structure à joindre à un ticket (sérialisable avec la
+// feature `serde`).
+//
+// Le rapport ne fait jamais échouer l'appel : une valeur illisible (capteur non
+// initialisé, lien coupé) reste à `None`. Il parle au capteur (version, IDs, court
+// test du lien) : à lancer hors des heures de passage, comme `probe_link`.
+//
+// `read_control_lines`, à l'inverse, ne parle pas au capteur : il relit les GPIO IRQ
+// et RST, pour séparer un défaut de câblage d'un firmware muet à la mise en service.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use anyhow::Result;
+
+use super::bep;
+use super::health::HealthEstimate;
+use super::probe::LinkQuality;
+use super::security::SecurityState;
+use super::sensor::{
+    not_initialized, NoMatchReason, OpTiming, SensorCtx, DEFAULT_TEMPLATE_CAPACITY, HARDWARE_ID_LEN,
+};
+use super::stats::TemplateStats;
+
+/// Paquets de test envoyés par `diagnostics` pour mesurer le lien.
+pub const DIAGNOSTICS_PROBE_ITERATIONS: u32 = 20;
+
+/// Photographie de l'état du module et du capteur (voir `SensorCtx::diagnostics`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DiagnosticsReport {
+    pub initialized: bool,
+    pub firmware: Option<String>,
+    /// Dernier identifiant matériel lu (conservé après `deinit`)
+    pub hardware_id: Option<[u8; HARDWARE_ID_LEN]>,
+    /// Relu sur le capteur, sinon dernière valeur en cache
+    pub template_count: Option<u16>,
+    pub template_capacity: u16,
+    pub health: HealthEstimate,
+    /// Statistiques d'usage connues, triées par ID
+    pub template_stats: Vec<(u16, TemplateStats)>,
+    /// Dernière erreur d'une identification, d'un enrôlement ou d'une calibration
+    pub last_error: Option<String>,
+    pub last_no_match: Option<NoMatchReason>,
+    /// Durées de la dernière opération avant le rapport
+    pub last_timing: Option<OpTiming>,
+    pub security: SecurityState,
+    /// Test de `DIAGNOSTICS_PROBE_ITERATIONS` échanges (`None` si impossible)
+    pub link: Option<LinkQuality>,
+}
+
+/// Niveaux électriques des lignes de contrôle (voir `SensorCtx::read_control_lines`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ControlLineState {
+    /// IRQ haute : le BM-Lite a une réponse prête (active haute)
+    pub irq: bool,
+    /// RST haute : BM-Lite hors reset (actif bas)
+    pub rst: bool,
+}
+
+impl SensorCtx {
+    /// Relit les GPIO IRQ et RST configurés, sans rien envoyer au capteur ni changer
+    /// leur état : contrôle de câblage sans analyseur logique.
+    ///
+    /// Au repos, RST est haute (basse : veille via `suspend`, ou ligne à la masse) et
+    /// IRQ basse : le capteur la monte quand une réponse est prête (doigt détecté,
+    /// fin de commande) et elle redescend une fois la réponse lue. IRQ haute entre
+    /// deux commandes, ou RST basse hors veille, oriente vers le câblage ou le
+    /// tirage (`PinConfig::irq_line`, `rst_line`) plutôt que vers le firmware.
+    pub fn read_control_lines(&self) -> Result<ControlLineState> {
+        let board = self.board.as_ref().ok_or_else(not_initialized)?;
+        let (irq, rst) = bep::control_lines(board);
+        Ok(ControlLineState { irq, rst })
+    }
+
+    /// Rassemble l'état du module et du capteur en un rapport de support.
+    pub fn diagnostics(&mut self) -> DiagnosticsReport {
+        // Avant le test du lien, qui remplace dernière erreur et durées
+        let last_error = self.last_error.clone();
+        let last_timing = self.last_timing;
+
+        let firmware = self
+            .chain
+            .as_mut()
+            .and_then(|chain| chain.firmware_version().ok());
+        let template_count = match self.refresh_cache() {
+            Ok(ids) => Some(ids.len() as u16),
+            Err(_) => self.enrolled.count(),
+        };
+        let link = self
+            .chain
+            .is_some()
+            .then(|| self.probe_link(DIAGNOSTICS_PROBE_ITERATIONS).ok())
+            .flatten();
+        self.last_error = last_error.clone();
+        self.last_timing = last_timing;
+
+        DiagnosticsReport {
+            initialized: self.is_initialized(),
+            firmware,
+            hardware_id: self.unique_id,
+            template_count,
+            template_capacity: self.template_capacity.unwrap_or(DEFAULT_TEMPLATE_CAPACITY),
+            health: self.sensor_health(),
+            template_stats: self.all_template_stats(),
+            last_error,
+            last_no_match: self.last_no_match,
+            last_timing,
+            security: self.security_state(),
+            link,
+        }
+    }
+}
@@ -0,0 +1,663 @@
+#![forbid(unsafe_code)]
+
+// Enrôlement piloté côté Rust (équivalent de bep_enroll_finger, avec politique de
+// tolérance aux mauvais échantillons).
+//
+// Pas de reprise après une erreur de lien : sur une trame perdue, l'hôte ne sait pas
+// si le firmware a exécuté la commande (échantillon ajouté ou non), et rien dans HCP
+// ne permet de relire l'état d'un enrôlement en cours. L'enrôlement partiel est donc
+// abandonné (ENROLL FINISH, sans sauvegarde) et `FingerprintError::EnrollInterrupted`
+// indique à l'appelant de recommencer depuis le début.
+
+use anyhow::{anyhow, Result};
+
+use super::bep::{self, Chain};
+use super::error::{
+    is_link_error, FingerprintError, BEP_RESULT_GENERAL_ERROR, BEP_RESULT_ID_NOT_FOUND,
+};
+use super::sensor::{not_initialized, SensorCtx};
+
+// Nombre maximal de captures par enrôlement (MAX_CAPTURE_ATTEMPTS côté C)
+const MAX_CAPTURE_ATTEMPTS: u8 = 15;
+// Délai de capture par échantillon (CAPTURE_TIMEOUT côté C)
+const CAPTURE_TIMEOUT_MS: u16 = 3000;
+
+/// Par défaut, autant de mauvais échantillons que de tentatives : même comportement
+/// que la boucle C d'origine.
+pub const DEFAULT_MAX_BAD_SAMPLES: u8 = MAX_CAPTURE_ATTEMPTS;
+
+// Réglages d'enrôlement portés par le `SensorCtx`
+pub(super) struct EnrollSettings {
+    pub max_bad_samples: u8,
+    pub preparation: EnrollPreparation,
+    pub require_lift: bool,
+}
+
+impl EnrollSettings {
+    pub(super) const fn new() -> Self {
+        Self {
+            max_bad_samples: DEFAULT_MAX_BAD_SAMPLES,
+            preparation: EnrollPreparation::None,
+            require_lift: true,
+        }
+    }
+}
+
+/// Préparation du capteur avant `enroll_user`, `enroll_user_if_needed` et
+/// `enroll_next`, dans l'ordre indiqué (voir `SensorCtx::set_enroll_preparation`).
+///
+/// La calibration est écrite en flash BM-Lite et remplace la précédente, calibration
+/// d'usine comprise ; elle n'est utilisée qu'après un reset du capteur. Le reset
+/// (matériel) vide la RAM BM-Lite (enrôlement partiel, template chargé) et ne touche
+/// ni la flash ni les templates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EnrollPreparation {
+    /// Aucune étape : calibration et état du capteur conservés
+    #[default]
+    None,
+    /// Reset seul : RAM BM-Lite vidée, calibration en place conservée et active
+    Reset,
+    /// Calibration seule : nouvelle calibration en flash, mais l'ancienne reste active
+    /// jusqu'au prochain reset (`reconnect`, coupure) ; l'enrôlement se fait avec elle
+    Calibrate,
+    /// Calibration puis reset : nouvelle calibration en flash et active pour
+    /// l'enrôlement (comportement de `set_calibrate_on_enroll(true)`)
+    CalibrateThenReset,
+}
+
+// Étapes de `run_enroll`, pour le log de l'étape en échec et le rollback
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EnrollStep {
+    Calibrate,
+    Capture,
+    Save,
+    Confirm,
+}
+
+impl EnrollStep {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Calibrate => "préparation (calibration / reset)",
+            Self::Capture => "enrôlement",
+            Self::Save => "sauvegarde",
+            Self::Confirm => "relecture des templates",
+        }
+    }
+}
+
+/// Que faire dans `enroll_user_if_needed` quand le capteur a déjà un template.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExistingPolicy {
+    /// Garder l'existant et ne rien enrôler (warning dans les logs)
+    #[default]
+    Skip,
+    /// Effacer (`wipe_templates`, templates épinglés conservés) puis enrôler. Si l'ID 1
+    /// porte un template épinglé, rien n'est effacé et `FingerprintError::AlreadyEnrolled` est renvoyé
+    Overwrite,
+    /// Renvoyer `FingerprintError::AlreadyEnrolled`
+    Error,
+}
+
+/// Bilan d'un enrôlement réussi.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnrollReport {
+    pub template_id: u16,
+    /// Échantillons acceptés par le firmware
+    pub accepted_samples: u8,
+    /// Captures ratées ou échantillons refusés (doigt sale, mal placé, ...)
+    pub rejected_samples: u8,
+}
+
+impl EnrollReport {
+    /// Part d'échantillons acceptés, de 0 à 100 (voir `SensorCtx::template_quality`).
+    pub fn quality(&self) -> u16 {
+        let total = u16::from(self.accepted_samples) + u16::from(self.rejected_samples);
+        if total == 0 {
+            return 0;
+        }
+        u16::from(self.accepted_samples) * 100 / total
+    }
+}
+
+// Boucle start -> (capture, add)* -> finish. Le firmware n'expose pas de score de
+// qualité : un échantillon est "mauvais" si la capture ou l'ajout est refusé.
+fn enroll_finger(
+    chain: &mut Chain,
+    capture_timeout_ms: u16,
+    settings: &EnrollSettings,
+    log_target: &'static str,
+) -> Result<(u8, u8)> {
+    let max_bad_samples = settings.max_bad_samples;
+    chain.enroll_start()?;
+
+    let mut accepted: u8 = 0;
+    let mut rejected: u8 = 0;
+    let mut done = false;
+
+    for attempt in 1..=MAX_CAPTURE_ATTEMPTS {
+        let sample = chain
+            .capture(capture_timeout_ms)
+            .and_then(|()| chain.enroll_add());
+        match sample {
+            Ok(remaining) => {
+                accepted += 1;
+                fp_info!(target: log_target, "Échantillon {attempt} accepté, restants: {remaining}");
+                if remaining == 0 {
+                    done = true;
+                    break;
+                }
+                if settings.require_lift {
+                    let _ = chain.wait_finger_not_present(0);
+                }
+            }
+            Err(e) if is_link_error(&e) => {
+                fp_warn!(target: log_target, "Échantillon {attempt}: erreur de lien ({e}), enrôlement abandonné");
+                let _ = chain.enroll_finish();
+                return Err(FingerprintError::EnrollInterrupted {
+                    accepted_samples: accepted,
+                }
+                .into());
+            }
+            Err(e) => {
+                rejected += 1;
+                fp_warn!(target: log_target, "Échantillon {attempt} refusé ({rejected}/{max_bad_samples}): {e}");
+                if rejected >= max_bad_samples {
+                    let _ = chain.enroll_finish();
+                    return Err(FingerprintError::LowQuality {
+                        bad_samples: rejected,
+                    }
+                    .into());
+                }
+            }
+        }
+    }
+
+    chain.enroll_finish()?;
+    if !done {
+        return Err(FingerprintError::Bep {
+            what: "enroll",
+            code: BEP_RESULT_GENERAL_ERROR,
+        }
+        .into());
+    }
+    Ok((accepted, rejected))
+}
+
+impl SensorCtx {
+    /// Nombre de captures ratées / refusées tolérées avant d'abandonner l'enrôlement
+    /// avec `FingerprintError::LowQuality` (défaut : `DEFAULT_MAX_BAD_SAMPLES`).
+    pub fn set_enroll_retry(&mut self, max_bad_samples: u8) -> Result<()> {
+        self.check_unlocked("set_enroll_retry")?;
+        self.enroll.max_bad_samples = max_bad_samples.max(1);
+        Ok(())
+    }
+
+    /// Attendre que le doigt soit levé entre deux échantillons d'enrôlement (défaut :
+    /// oui). C'est l'hôte qui attend, pas le firmware : sans retrait, l'enrôlement
+    /// reste bloqué sur cette attente, sans limite de temps.
+    ///
+    /// À `false`, l'échantillon suivant est capturé dès que le précédent est accepté,
+    /// doigt toujours posé : plus d'attente, mais des échantillons quasi identiques
+    /// qui couvrent une plus petite zone du doigt, donc plus de faux rejets ensuite
+    /// dès que le doigt est posé un peu autrement. À réserver aux bornes qui guident
+    /// l'utilisateur elles-mêmes (« décale légèrement ton doigt ») entre les captures.
+    pub fn set_require_lift(&mut self, require: bool) -> Result<()> {
+        self.check_unlocked("set_require_lift")?;
+        self.enroll.require_lift = require;
+        Ok(())
+    }
+
+    /// Surface minimale du capteur (en %) qu'un échantillon d'enrôlement doit couvrir.
+    ///
+    /// Le protocole HCP du BM-Lite n'a pas ce réglage (aucun argument de couverture
+    /// dans CMD_SETTINGS / CMD_ENROLL) : une fois `pct` validé (1 à 100), renvoie
+    /// `FingerprintError::Unsupported` et le critère reste celui du firmware.
+    pub fn set_enroll_coverage(&mut self, pct: u8) -> Result<()> {
+        self.check_unlocked("set_enroll_coverage")?;
+        if self.chain.is_none() {
+            return Err(not_initialized());
+        }
+        if !(1..=100).contains(&pct) {
+            return Err(anyhow!("invalid enroll coverage {pct}%"));
+        }
+        Err(FingerprintError::Unsupported("enroll coverage").into())
+    }
+
+    /// Nombre d'orientations du doigt à enrôler (une série d'échantillons chacune),
+    /// pour mieux reconnaître un doigt posé de travers. Chaque orientation ajoutée
+    /// rallonge l'enrôlement d'autant : `n` fois plus de captures.
+    ///
+    /// Le firmware BM-Lite enrôle une seule orientation et le protocole HCP n'a pas de
+    /// réglage multi-angles (`Capabilities::enroll_orientations` vaut `false`) : `1`
+    /// est accepté, toute autre valeur (validée, au moins 1) renvoie
+    /// `FingerprintError::Unsupported`.
+    pub fn set_enroll_orientations(&mut self, n: u8) -> Result<()> {
+        self.check_unlocked("set_enroll_orientations")?;
+        if self.chain.is_none() {
+            return Err(not_initialized());
+        }
+        match n {
+            0 => Err(anyhow!("invalid enroll orientations: 0")),
+            1 => Ok(()),
+            _ => Err(FingerprintError::Unsupported("enroll orientations").into()),
+        }
+    }
+
+    /// Nombre minimal de zones distinctes du doigt à couvrir pendant l'enrôlement :
+    /// un échantillon qui reprend une zone déjà vue (même appui au centre) serait
+    /// refusé, avec une invite à décaler le doigt, jusqu'à atteindre `n` zones.
+    ///
+    /// Le firmware BM-Lite ne dit pas si un échantillon apporte une zone nouvelle :
+    /// CMD_ENROLL/ARG_ADD ne renvoie que le nombre d'échantillons restants
+    /// (`Capabilities::enroll_area_novelty` vaut `false`). `0` et `1` (aucune
+    /// contrainte) sont acceptés, toute autre valeur renvoie
+    /// `FingerprintError::Unsupported`. À défaut, garder `set_require_lift(true)` et
+    /// demander à l'utilisateur de décaler le doigt entre deux poses.
+    pub fn set_min_distinct_areas(&mut self, n: u8) -> Result<()> {
+        self.check_unlocked("set_min_distinct_areas")?;
+        if self.chain.is_none() {
+            return Err(not_initialized());
+        }
+        match n {
+            0 | 1 => Ok(()),
+            _ => Err(FingerprintError::Unsupported("enroll distinct areas").into()),
+        }
+    }
+
+    /// Étapes exécutées avant chaque enrôlement (voir `EnrollPreparation`). Aucune par
+    /// défaut, comme l'appel `calibrate` dans `init` : la calibration persiste en flash
+    /// BM-Lite, la faire une fois à l'installation (ou en usine) suffit, et recalibrer
+    /// sur le terrain écraserait celle d'usine.
+    pub fn set_enroll_preparation(&mut self, preparation: EnrollPreparation) -> Result<()> {
+        self.check_unlocked("set_enroll_preparation")?;
+        self.enroll.preparation = preparation;
+        Ok(())
+    }
+
+    /// Raccourci : `EnrollPreparation::CalibrateThenReset` si `enabled`, sinon
+    /// `EnrollPreparation::None`.
+    pub fn set_calibrate_on_enroll(&mut self, enabled: bool) -> Result<()> {
+        self.set_enroll_preparation(if enabled {
+            EnrollPreparation::CalibrateThenReset
+        } else {
+            EnrollPreparation::None
+        })
+    }
+
+    // Exécute `enroll.preparation` ; sur une calibration ratée, rien n'est enrôlé
+    fn prepare_enroll(&mut self) -> Result<()> {
+        let preparation = self.enroll.preparation;
+        if matches!(
+            preparation,
+            EnrollPreparation::Calibrate | EnrollPreparation::CalibrateThenReset
+        ) {
+            self.run_calibrate(|_| {})?;
+        }
+        if matches!(
+            preparation,
+            EnrollPreparation::Reset | EnrollPreparation::CalibrateThenReset
+        ) {
+            let board = self.board.as_mut().ok_or_else(not_initialized)?;
+            bep::hw_reset(board);
+        }
+        Ok(())
+    }
+
+    // Init : un reboot de l'hôte en plein enrôlement laisse le BM-Lite en attente
+    // d'échantillons, et le START suivant échoue. Le reset matériel de `init_board`
+    // purge normalement cet état ; sans RST effectif, on ferme l'enrôlement à la main.
+    // Le firmware n'a pas de commande d'état : FINISH est envoyé à chaque init, et
+    // n'est accepté que si un enrôlement était ouvert (refusé sinon, sans effet).
+    pub(super) fn abort_stale_enroll(&mut self) {
+        let Some(chain) = self.chain.as_mut() else {
+            return;
+        };
+        if chain.enroll_abort().is_ok() {
+            fp_warn!(target: self.log_target, "BM-Lite: enrôlement resté ouvert avant le reboot, abandonné");
+        }
+    }
+
+    /// Enrôle un doigt sous l'ID 1 (template existant remplacé).
+    ///
+    /// Tout ou rien pour le stockage : si la capture, la sauvegarde ou sa relecture
+    /// échoue, le slot est remis dans son état d'avant (vide, ou ancien template
+    /// réimporté) et l'étape en échec est loguée. Seule une calibration faite par
+    /// `EnrollPreparation` reste en place, la flash BM-Lite ne gardant pas l'ancienne.
+    /// Vaut aussi pour `enroll_user_if_needed` et `enroll_next`.
+    pub fn enroll_user(&mut self) -> Result<EnrollReport> {
+        self.timed(|ctx| ctx.run_enroll(1))
+    }
+
+    /// Enrôle un doigt sous l'ID 1 si le capteur est vide ; sinon applique `policy`.
+    /// Retourne `None` si rien n'a été enrôlé (`ExistingPolicy::Skip`).
+    pub fn enroll_user_if_needed(
+        &mut self,
+        policy: ExistingPolicy,
+    ) -> Result<Option<EnrollReport>> {
+        if self.is_user_enrolled()? {
+            // Un template 1 épinglé survivrait au wipe, puis serait écrasé par l'enrôlement
+            let pinned_user = self.pinned.contains(1) && self.enrolled_ids()?.contains(&1);
+            match policy {
+                ExistingPolicy::Skip => {
+                    fp_warn!(target: self.log_target, "Template déjà présent, enrôlement ignoré");
+                    return Ok(None);
+                }
+                ExistingPolicy::Error => return Err(FingerprintError::AlreadyEnrolled.into()),
+                ExistingPolicy::Overwrite if pinned_user => {
+                    fp_warn!(target: self.log_target, "Template 1 épinglé : enrôlement refusé");
+                    return Err(FingerprintError::AlreadyEnrolled.into());
+                }
+                ExistingPolicy::Overwrite => {
+                    let report = self.wipe_templates(false)?;
+                    fp_info!(target: self.log_target, "Templates effacés avant enrôlement : {}", report.removed);
+                }
+            }
+        }
+        self.enroll_user().map(Some)
+    }
+
+    /// Enrôle dans le plus petit emplacement libre (voir `free_slots`) et retourne
+    /// l'ID attribué. `FingerprintError::StoreFull` si tous sont occupés.
+    pub fn enroll_next(&mut self) -> Result<u16> {
+        let id = *self
+            .free_slots()?
+            .first()
+            .ok_or(FingerprintError::StoreFull)?;
+        Ok(self.timed(|ctx| ctx.run_enroll(id))?.template_id)
+    }
+
+    fn run_enroll(&mut self, template_id: u16) -> Result<EnrollReport> {
+        self.check_unlocked("enroll")?;
+
+        let backup = self.slot_backup(template_id)?;
+
+        let (accepted, rejected) = match self.enroll_steps(template_id) {
+            Ok(samples) => samples,
+            Err((step, e)) => {
+                fp_error!(target: self.log_target, "Enrôlement du template {template_id}: échec à l'étape {}: {e}", step.name());
+                match step {
+                    // Calibration en flash : ne se défait pas (voir `EnrollPreparation`)
+                    EnrollStep::Calibrate => {}
+                    // Template au mieux en RAM capteur : le stockage n'a pas été touché
+                    EnrollStep::Capture => {
+                        if let Some(chain) = self.chain.as_mut() {
+                            let _ = chain.enroll_abort();
+                        }
+                    }
+                    // Écriture peut-être faite malgré l'erreur : on remet le slot en état
+                    EnrollStep::Save | EnrollStep::Confirm => {
+                        self.restore_slot(template_id, backup.as_deref())
+                    }
+                }
+                return Err(e);
+            }
+        };
+        self.enrolled.insert(template_id);
+
+        // TRÈS IMPORTANT :
+        // attendre que le doigt soit retiré avant toute identification. Le template est
+        // stocké et vérifié : un doigt laissé en place n'annule pas l'enrôlement
+        fp_info!(target: self.log_target, "Enrôlement terminé. Lève ton doigt...");
+        if let Some(chain) = self.chain.as_mut() {
+            if let Err(e) = chain.wait_finger_not_present(5000) {
+                fp_warn!(target: self.log_target, "Doigt toujours posé après l'enrôlement: {e}");
+            }
+        }
+
+        // Petite pause pour laisser le module se stabiliser
+        self.clock.sleep_ms(150);
+
+        Ok(self.record_quality(EnrollReport {
+            template_id,
+            accepted_samples: accepted,
+            rejected_samples: rejected,
+        }))
+    }
+
+    // Préparation, enrôlement, sauvegarde et relecture, en renvoyant l'étape en échec
+    fn enroll_steps(&mut self, template_id: u16) -> Result<(u8, u8), (EnrollStep, anyhow::Error)> {
+        self.prepare_enroll()
+            .map_err(|e| (EnrollStep::Calibrate, e))?;
+
+        let chain = self
+            .chain
+            .as_mut()
+            .ok_or_else(|| (EnrollStep::Capture, not_initialized()))?;
+
+        fp_info!(target: self.log_target, "Enrôlement : pose ton doigt...");
+
+        // 1) Enrôlement
+        let samples = enroll_finger(chain, CAPTURE_TIMEOUT_MS, &self.enroll, self.log_target)
+            .map_err(|e| (EnrollStep::Capture, e))?;
+
+        // 2) Sauvegarde du template
+        chain
+            .template_save(template_id)
+            .map_err(|e| (EnrollStep::Save, e))?;
+
+        // 3) Vérification que le template est bien stocké
+        let ids = chain.template_ids().map_err(|e| (EnrollStep::Confirm, e))?;
+        if !ids.contains(&template_id) {
+            let e = FingerprintError::Bep {
+                what: "template_save",
+                code: BEP_RESULT_ID_NOT_FOUND,
+            };
+            return Err((EnrollStep::Confirm, e.into()));
+        }
+        fp_info!(target: self.log_target, "Templates après save: {}", ids.len());
+        Ok(samples)
+    }
+
+    // Contenu du slot `id` avant un enrôlement : c'est lui qu'un échec restaure. À lire
+    // avant toute étape, l'export passant par la RAM capteur
+    fn slot_backup(&mut self, id: u16) -> Result<Option<Vec<u8>>> {
+        if !self.enrolled_ids()?.contains(&id) {
+            return Ok(None);
+        }
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+        chain.template_export(id).map(Some)
+    }
+
+    // Remet le slot `id` dans son état d'avant l'enrôlement : vide, ou `backup`
+    fn restore_slot(&mut self, id: u16, backup: Option<&[u8]>) {
+        let Some(chain) = self.chain.as_mut() else {
+            self.enrolled.invalidate();
+            return;
+        };
+        // Le slot peut être vide (écriture jamais faite) : ID_NOT_FOUND n'est pas une
+        // erreur ici
+        let removed = match chain.template_remove(id) {
+            Err(e)
+                if !matches!(
+                    FingerprintError::of(&e),
+                    Some(FingerprintError::Bep {
+                        code: BEP_RESULT_ID_NOT_FOUND,
+                        ..
+                    })
+                ) =>
+            {
+                Err(e)
+            }
+            _ => Ok(()),
+        };
+        let restored = match backup {
+            Some(data) => removed.and_then(|()| chain.template_import(id, data)),
+            None => removed,
+        };
+        match restored {
+            Ok(()) if backup.is_some() => {
+                fp_warn!(target: self.log_target, "Template {id}: ancien template restauré");
+            }
+            Ok(()) => {
+                self.enrolled.remove(id);
+                fp_warn!(target: self.log_target, "Template {id}: slot remis à vide");
+            }
+            Err(e) => {
+                fp_error!(target: self.log_target, "Template {id}: restauration impossible: {e}");
+                self.enrolled.invalidate();
+                self.mirror.invalidate();
+                self.qualities.remove(id);
+                self.checksums.remove(id);
+                self.stats.remove(id);
+            }
+        }
+    }
+
+    // Mémorise la qualité du template enregistré (voir `template_quality`)
+    fn record_quality(&mut self, report: EnrollReport) -> EnrollReport {
+        self.qualities.insert(report.template_id, report.quality());
+        // Template réécrit : l'ancienne référence d'intégrité ne vaut plus
+        self.checksums.remove(report.template_id);
+        self.record_enrolled(report.template_id);
+        self.mirror_fetch(report.template_id);
+        fp_info!(target: self.log_target, "Template {}: qualité d'enrôlement {}%", report.template_id, report.quality());
+        report
+    }
+
+    /// Enrôle sous `id`, sauvegarde, puis demande un scan de vérification qui doit
+    /// reconnaître `id`. Sinon (autre template, pas de match, timeout), ou si la
+    /// sauvegarde échoue, le slot est remis dans son état d'avant comme pour
+    /// `enroll_user` (vide, ou ancien template réimporté) et l'erreur remontée
+    /// (`FingerprintError::VerificationFailed` sur un mauvais match) : tout template
+    /// conservé a été reconnu au moins une fois. `timeout_ms` s'applique à chaque
+    /// capture et au scan de vérification.
+    pub fn enroll_and_verify(&mut self, id: u16, timeout_ms: u32) -> Result<EnrollReport> {
+        self.timed(|ctx| ctx.run_enroll_and_verify(id, timeout_ms))
+    }
+
+    fn run_enroll_and_verify(&mut self, id: u16, timeout_ms: u32) -> Result<EnrollReport> {
+        self.check_unlocked("enroll")?;
+        let backup = self.slot_backup(id)?;
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+
+        fp_info!(target: self.log_target, "Enrôlement du template {id} : pose ton doigt...");
+        let capture_timeout = timeout_ms.clamp(1, u32::from(u16::MAX)) as u16;
+        let (accepted, rejected) =
+            match enroll_finger(chain, capture_timeout, &self.enroll, self.log_target) {
+                Ok(samples) => samples,
+                // Template au mieux en RAM capteur : le stockage n'a pas été touché
+                Err(e) => {
+                    let _ = chain.enroll_abort();
+                    return Err(e);
+                }
+            };
+        // Écriture peut-être faite malgré l'erreur : on remet le slot en état
+        if let Err(e) = chain.template_save(id) {
+            fp_error!(target: self.log_target, "Template {id}: échec de la sauvegarde: {e}");
+            self.restore_slot(id, backup.as_deref());
+            return Err(e);
+        }
+        self.enrolled.insert(id);
+
+        fp_info!(target: self.log_target, "Vérification : lève puis repose ton doigt...");
+        let _ = chain.wait_finger_not_present(5000);
+        let verified = self.verification_scan(capture_timeout);
+        if let Ok(Some(matched)) = verified {
+            if matched == id {
+                fp_info!(target: self.log_target, "Template {id} vérifié");
+                return Ok(self.record_quality(EnrollReport {
+                    template_id: id,
+                    accepted_samples: accepted,
+                    rejected_samples: rejected,
+                }));
+            }
+        }
+
+        // Rollback : le template n'a pas été reconnu, on ne le garde pas
+        fp_warn!(target: self.log_target, "Template {id} non vérifié, enrôlement annulé");
+        self.restore_slot(id, backup.as_deref());
+        match verified {
+            Err(e) => Err(e),
+            Ok(matched) => Err(FingerprintError::VerificationFailed { id, matched }.into()),
+        }
+    }
+
+    // Scan de vérification : attente doigt -> identification -> attente retrait, comme
+    // `identify_attempt` mais sans statistiques ni dernier match, le doigt reconnu
+    // n'étant pas un accès (et l'enrôlement pouvant encore être annulé)
+    fn verification_scan(&mut self, timeout_ms: u16) -> Result<Option<u16>> {
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+        chain.wait_finger_present(timeout_ms)?;
+        let matched = chain.identify_finger(timeout_ms.into())?;
+        let _ = chain.wait_finger_not_present(5000);
+        if let Some(tid) = matched {
+            self.check_matched_id(tid)?;
+        }
+        Ok(matched)
+    }
+
+    /// Ré-enrôle le doigt du template `id` sans changer d'ID (coupure, usure, ...).
+    ///
+    /// Le nouvel enrôlement reste en RAM capteur jusqu'au bout : s'il échoue
+    /// (`LowQuality`, timeout, ...), l'ancien template n'a pas été touché. Ensuite
+    /// l'ancien est sauvegardé côté hôte, effacé puis remplacé ; si l'écriture échoue,
+    /// il est réimporté et `FingerprintError::UpdateFailed { preserved }` indique si
+    /// la restauration a réussi. `timeout_ms` s'applique à chaque capture.
+    pub fn update_template(&mut self, id: u16, timeout_ms: u32) -> Result<EnrollReport> {
+        self.timed(|ctx| ctx.run_update(id, timeout_ms))
+    }
+
+    /// Ajoute de nouvelles captures au template `id` au lieu de le remplacer, pour
+    /// renforcer sur plusieurs sessions le template d'un doigt difficile ; le rapport
+    /// donnerait la qualité résultante.
+    ///
+    /// Le firmware BM-Lite n'enrôle que dans un template neuf (ENROLL START repart de
+    /// zéro, et HCP n'a pas de commande pour rouvrir un template stocké) : une fois `id`
+    /// vérifié, renvoie `FingerprintError::Unsupported` sans rien capturer. À défaut,
+    /// `update_template` ré-enrôle le doigt sous le même ID.
+    pub fn augment_template(&mut self, id: u16, _timeout_ms: u32) -> Result<EnrollReport> {
+        self.check_unlocked("augment_template")?;
+        if !self.enrolled_ids()?.contains(&id) {
+            return Err(FingerprintError::Bep {
+                what: "augment_template",
+                code: BEP_RESULT_ID_NOT_FOUND,
+            }
+            .into());
+        }
+        Err(FingerprintError::Unsupported("template augmentation").into())
+    }
+
+    fn run_update(&mut self, id: u16, timeout_ms: u32) -> Result<EnrollReport> {
+        self.check_unlocked("update_template")?;
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+
+        if !chain.template_ids()?.contains(&id) {
+            return Err(FingerprintError::Bep {
+                what: "update_template",
+                code: BEP_RESULT_ID_NOT_FOUND,
+            }
+            .into());
+        }
+        // Avant l'enrôlement : l'export passe par la RAM capteur
+        let backup = chain.template_export(id)?;
+
+        fp_info!(target: self.log_target, "Mise à jour du template {id} : pose ton doigt...");
+        let capture_timeout = timeout_ms.clamp(1, u32::from(u16::MAX)) as u16;
+        let (accepted, rejected) =
+            enroll_finger(chain, capture_timeout, &self.enroll, self.log_target)?;
+
+        let written = chain
+            .template_remove(id)
+            .and_then(|()| chain.template_save(id));
+        if let Err(e) = written {
+            fp_error!(target: self.log_target, "Template {id}: écriture impossible: {e}");
+            let preserved = chain.template_import(id, &backup).is_ok();
+            if !preserved {
+                self.enrolled.invalidate();
+                self.mirror.invalidate();
+                self.qualities.remove(id);
+                self.checksums.remove(id);
+                self.stats.remove(id);
+            }
+            return Err(FingerprintError::UpdateFailed { id, preserved }.into());
+        }
+
+        let _ = chain.wait_finger_not_present(5000);
+        fp_info!(target: self.log_target, "Template {id} mis à jour");
+        Ok(self.record_quality(EnrollReport {
+            template_id: id,
+            accepted_samples: accepted,
+            rejected_samples: rejected,
+        }))
+    }
+}
@@ -0,0 +1,215 @@
+#![forbid(unsafe_code)]
+
+use core::fmt;
+
+// Codes fpc_bep_result_t utiles côté Rust (fpc_bep_types.h)
+#[cfg(any(feature = "enroll", feature = "cabi"))]
+pub(super) const BEP_RESULT_GENERAL_ERROR: i32 = -1;
+pub(super) const BEP_RESULT_INVALID_ARGUMENT: i32 = -3;
+pub(super) const BEP_RESULT_NOT_IMPLEMENTED: i32 = -4;
+pub(super) const BEP_RESULT_IO_ERROR: i32 = -8;
+pub(super) const BEP_RESULT_TIMEOUT: i32 = -11;
+#[cfg(any(feature = "enroll", feature = "sim"))]
+pub(super) const BEP_RESULT_ID_NOT_FOUND: i32 = -13;
+pub(super) const BEP_RESULT_IMAGE_CAPTURE_ERROR: i32 = -15;
+pub(super) const BEP_RESULT_INVALID_CALIBRATION: i32 = -19;
+pub(super) const BEP_RESULT_NOT_SUPPORTED: i32 = -24;
+
+/// Erreurs typées du module. Elles sont renvoyées dans un `anyhow::Error` :
+/// l'appelant les récupère avec `err.downcast_ref::<FingerprintError>()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FingerprintError {
+    /// Opération capteur appelée avant `init()` ou après `deinit()`
+    NotInitialized,
+    /// Le capteur (ou le lien SPI) n'a pas répondu dans le délai imparti
+    Timeout(&'static str),
+    /// Commande BM-Lite en échec avec un code `fpc_bep_result_t`
+    Bep { what: &'static str, code: i32 },
+    /// Enrôlement abandonné : trop d'échantillons de mauvaise qualité
+    LowQuality { bad_samples: u8 },
+    /// Mise à jour d'un template en échec après effacement de l'ancien ; `preserved`
+    /// indique si l'ancien a pu être restauré sous le même ID
+    UpdateFailed { id: u16, preserved: bool },
+    /// Attente interrompue par un `CancelToken`
+    Cancelled,
+    /// Données importées altérées ou clé incorrecte (tag d'authentification invalide)
+    Integrity,
+    /// Capteur jamais calibré (ou calibration effacée) : lancer `calibrate()`
+    NotCalibrated(&'static str),
+    /// Sauvegarde (`import_all`) dans une version de format inconnue
+    UnsupportedBackup(u8),
+    /// Octets qui ne peuvent pas être un template BM-Lite (raison), refusés avant
+    /// tout envoi au capteur (voir `validate_template_bytes`)
+    IncompatibleTemplate(&'static str),
+    /// Le scan de vérification après enrôlement n'a pas reconnu le template `id`
+    /// (`matched` : autre template reconnu, le cas échéant) ; le slot a été remis dans
+    /// son état d'avant l'enrôlement
+    VerificationFailed { id: u16, matched: Option<u16> },
+    /// Réglage ou commande absent du firmware BM-Lite
+    Unsupported(&'static str),
+    /// Plus aucun emplacement de template libre (`max_template_capacity` atteint)
+    StoreFull,
+    /// Un template existe déjà (`enroll_user_if_needed` avec `ExistingPolicy::Error`,
+    /// ou `Overwrite` quand le template 1 est épinglé)
+    AlreadyEnrolled,
+    /// `platform_init` a réussi mais la chaîne HCP est inutilisable (champ en cause)
+    InvalidChain(&'static str),
+    /// Erreurs de trame répétées alors que le capteur répond entre-temps : contention
+    /// probable sur un bus SPI partagé. Un capteur mort ou débranché donne au contraire
+    /// des `Bep { code: -8 }` (ou `Timeout`) sans réponse valide intercalée.
+    BusContention(&'static str),
+    /// Enrôlement interrompu par une erreur de lien après `accepted_samples`
+    /// échantillons : l'état partiel est abandonné, recommencer l'enrôlement
+    EnrollInterrupted { accepted_samples: u8 },
+    /// Opération d'administration refusée : configuration verrouillée (`lock_config`)
+    Locked(&'static str),
+    /// Identification refusée : trop de non-matchs consécutifs (`LockoutPolicy`),
+    /// nouvel essai possible dans `retry_in_ms`
+    LockedOut { retry_in_ms: u32 },
+}
+
+impl fmt::Display for FingerprintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotInitialized => write!(f, "BM-Lite not initialized"),
+            Self::Timeout(what) => write!(f, "{what} timed out"),
+            Self::Bep { what, code } => write!(f, "{what} failed with code {code}"),
+            Self::LowQuality { bad_samples } => {
+                write!(f, "enrollment aborted after {bad_samples} poor samples")
+            }
+            Self::UpdateFailed { id, preserved } => write!(
+                f,
+                "template {id} update failed, old template {}",
+                if *preserved { "restored" } else { "lost" }
+            ),
+            Self::Cancelled => write!(f, "operation cancelled"),
+            Self::Integrity => write!(f, "template integrity check failed"),
+            Self::NotCalibrated(what) => write!(f, "{what} failed: sensor not calibrated"),
+            Self::UnsupportedBackup(version) => {
+                write!(f, "backup format version {version} not supported")
+            }
+            Self::IncompatibleTemplate(why) => write!(f, "incompatible template: {why}"),
+            Self::VerificationFailed { id, matched } => match matched {
+                Some(other) => write!(f, "template {id} verification matched template {other}"),
+                None => write!(f, "template {id} verification found no match"),
+            },
+            Self::Unsupported(what) => write!(f, "{what} not supported by the sensor firmware"),
+            Self::StoreFull => write!(f, "template storage full"),
+            Self::AlreadyEnrolled => write!(f, "a template is already enrolled"),
+            Self::InvalidChain(field) => {
+                write!(f, "HCP chain invalid after platform_init: {field}")
+            }
+            Self::EnrollInterrupted { accepted_samples } => write!(
+                f,
+                "enrollment interrupted by a link error after {accepted_samples} samples"
+            ),
+            Self::Locked(what) => write!(f, "{what} refused: configuration locked"),
+            Self::LockedOut { retry_in_ms } => {
+                write!(f, "identification locked out, retry in {retry_in_ms} ms")
+            }
+            Self::BusContention(what) => {
+                write!(
+                    f,
+                    "{what} failed: repeated framing errors, likely SPI bus contention"
+                )
+            }
+        }
+    }
+}
+
+/// Suite à donner à une erreur (voir `FingerprintError::recovery_action`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryAction {
+    /// Rien de cassé : recommencer la même opération (au besoin après une pause,
+    /// ou avec l'utilisateur pour un doigt mal posé)
+    Transient,
+    /// Capteur ou lien dans un état douteux : `reconnect` (ou `init`), puis recommencer
+    Recoverable,
+    /// Recommencer ne changera rien : configuration, données ou opérateur à revoir
+    Fatal,
+}
+
+impl RecoveryAction {
+    /// Classe une erreur renvoyée par le module. Une erreur non typée (argument
+    /// invalide, erreur ESP-IDF à l'init) est `Fatal`.
+    pub fn of(err: &anyhow::Error) -> Self {
+        FingerprintError::of(err).map_or(Self::Fatal, |e| e.recovery_action())
+    }
+}
+
+// Pas d'impl `std::error::Error` (cœur sans std) : la conversion passe par `Error::msg`,
+// qui conserve le downcast vers `FingerprintError`.
+impl From<FingerprintError> for anyhow::Error {
+    fn from(e: FingerprintError) -> Self {
+        anyhow::Error::msg(e)
+    }
+}
+
+impl FingerprintError {
+    /// Extrait l'erreur typée d'un `anyhow::Error`, si c'en est une.
+    pub fn of(err: &anyhow::Error) -> Option<Self> {
+        err.downcast_ref::<Self>().copied()
+    }
+
+    /// Classement de l'erreur selon la suite à lui donner, pour qu'une boucle de
+    /// service réagisse partout de la même façon.
+    pub fn recovery_action(&self) -> RecoveryAction {
+        match self {
+            Self::Timeout(_)
+            | Self::LowQuality { .. }
+            | Self::UpdateFailed {
+                preserved: true, ..
+            }
+            | Self::Cancelled
+            | Self::VerificationFailed { .. }
+            | Self::BusContention(_)
+            | Self::EnrollInterrupted { .. }
+            | Self::LockedOut { .. } => RecoveryAction::Transient,
+            // Trame perdue, image ratée : le firmware est sain
+            Self::Bep {
+                code: BEP_RESULT_IO_ERROR | BEP_RESULT_IMAGE_CAPTURE_ERROR,
+                ..
+            } => RecoveryAction::Transient,
+            Self::Bep {
+                code: BEP_RESULT_INVALID_ARGUMENT,
+                ..
+            } => RecoveryAction::Fatal,
+            Self::NotInitialized | Self::Bep { .. } => RecoveryAction::Recoverable,
+            Self::UpdateFailed {
+                preserved: false, ..
+            }
+            | Self::Integrity
+            | Self::NotCalibrated(_)
+            | Self::UnsupportedBackup(_)
+            | Self::IncompatibleTemplate(_)
+            | Self::Unsupported(_)
+            | Self::StoreFull
+            | Self::AlreadyEnrolled
+            | Self::InvalidChain(_)
+            | Self::Locked(_) => RecoveryAction::Fatal,
+        }
+    }
+
+    pub(super) fn from_code(what: &'static str, code: i32) -> Self {
+        match code {
+            BEP_RESULT_TIMEOUT => Self::Timeout(what),
+            BEP_RESULT_INVALID_CALIBRATION => Self::NotCalibrated(what),
+            BEP_RESULT_NOT_IMPLEMENTED | BEP_RESULT_NOT_SUPPORTED => Self::Unsupported(what),
+            _ => Self::Bep { what, code },
+        }
+    }
+}
+
+// Erreur de communication (trame perdue, contention) : le capteur n'a pas répondu
+// correctement, par opposition à un refus métier (pas de match, mauvaise image)
+pub(super) fn is_link_error(err: &anyhow::Error) -> bool {
+    matches!(
+        FingerprintError::of(err),
+        Some(
+            FingerprintError::Bep {
+                code: BEP_RESULT_IO_ERROR,
+                ..
+            } | FingerprintError::BusContention(_)
+        )
+    )
+}
@@ -0,0 +1,109 @@
+use core::ffi::{c_char, c_void};
+
+use esp_idf_svc::sys::bmlite::{interface_t, pin_config_t};
+
+// ======================================================
+// 1) Structs BM-Lite corrigées (d'après hcp_tiny.h)
+// ======================================================
+
+#[repr(C)]
+pub struct HCP_arg_t {
+    pub size: u32,
+    pub data: *mut u8,
+}
+
+// `write` / `read` : callbacks C d'esp_hal.c, ou les trampolines de trace de bep.rs
+// qui les appellent (voir `Chain::set_packet_trace`)
+#[repr(C)]
+pub struct HCP_comm_t {
+    pub write: Option<unsafe extern "C" fn(u16, *const u8, u32) -> i32>,
+    pub read:  Option<unsafe extern "C" fn(u16, *mut u8, u32) -> i32>,
+    pub phy_rx_timeout: u32,
+    pub pkt_buffer: *mut u8,
+    pub pkt_size_max: u32,
+    pub pkt_size: u32,
+    pub txrx_buffer: *mut u8,
+    pub arg: HCP_arg_t,
+    pub bep_result: i32,
+}
+
+// ======================================================
+// 2) console_initparams_t équivalent Rust
+// ======================================================
+
+#[repr(C)]
+pub struct Params {
+    pub iface: interface_t,
+    pub port: *mut c_char,
+    pub baudrate: u32,
+    pub timeout: u32,
+    pub hcp_comm: *mut HCP_comm_t,
+    pub pins: *mut pin_config_t,
+}
+
+// ======================================================
+// 3) Déclarations externes C (bmlite_if.h, bmlite_hal.h, fpc_crc.h)
+// ======================================================
+
+extern "C" {
+    pub fn bep_enroll_finger(chain: *mut HCP_comm_t) -> i32;
+
+    pub fn bep_identify_finger(
+        chain: *mut HCP_comm_t,
+        timeout: u32,
+        template_id: *mut u16,
+        matched: *mut bool,
+    ) -> i32;
+
+    pub fn bep_sensor_calibrate(chain: *mut HCP_comm_t) -> i32;
+    pub fn bep_sw_reset(chain: *mut HCP_comm_t) -> i32;
+    pub fn bep_unique_id_get(chain: *mut HCP_comm_t, unique_id: *mut u8) -> i32;
+    pub fn bep_version(chain: *mut HCP_comm_t, version: *mut c_char, len: i32) -> i32;
+
+    pub fn bep_template_get_count(chain: *mut HCP_comm_t, count: *mut u16) -> i32;
+    pub fn bep_template_remove(chain: *mut HCP_comm_t, template_id: u16) -> i32;
+    pub fn bep_template_remove_all(chain: *mut HCP_comm_t) -> i32;
+    pub fn bep_template_get_ids(chain: *mut HCP_comm_t) -> i32;
+    pub fn bep_template_save(chain: *mut HCP_comm_t, id: u16) -> i32;
+    pub fn bep_template_load_storage(chain: *mut HCP_comm_t, template_id: u16) -> i32;
+    pub fn bep_template_get(chain: *mut HCP_comm_t, data: *mut u8, size: u32) -> i32;
+    pub fn bep_template_remove_ram(chain: *mut HCP_comm_t) -> i32;
+    pub fn bep_template_put(chain: *mut HCP_comm_t, data: *mut u8, length: u16) -> i32;
+    pub fn sensor_wait_finger_not_present(chain: *mut HCP_comm_t, timeout: u16) -> i32;
+    pub fn sensor_wait_finger_present(chain: *mut HCP_comm_t, timeout: u16) -> i32;
+    pub fn bep_capture(chain: *mut HCP_comm_t, timeout: u16) -> i32;
+
+    pub fn bmlite_send_cmd(chain: *mut HCP_comm_t, cmd: u16, arg_type: u16) -> i32;
+    pub fn bmlite_send_cmd_arg(
+        chain: *mut HCP_comm_t,
+        cmd: u16,
+        arg1_type: u16,
+        arg2_type: u16,
+        arg2_data: *mut c_void,
+        arg2_length: u16,
+    ) -> i32;
+    pub fn bmlite_get_arg(chain: *mut HCP_comm_t, arg_type: u16) -> i32;
+    pub fn bmlite_receive(chain: *mut HCP_comm_t) -> i32;
+
+    // Niveau de la ligne IRQ : le BM-Lite a une réponse prête
+    pub fn hal_bmlite_get_status() -> bool;
+    // RST (actif bas) : `true` maintient le BM-Lite en reset
+    pub fn hal_bmlite_reset(state: bool);
+    // Niveau relu de la broche RST (`true` = haut, hors reset)
+    pub fn hal_bmlite_get_reset_level() -> bool;
+
+    // Attente (vTaskDelay côté ESP) : évite de dépendre de std::thread dans le cœur
+    pub fn hal_timebase_busy_wait(ms: u32);
+    // Ticks en ms depuis le boot (hal_tick_t = uint64_t hors ARM)
+    pub fn hal_timebase_get_tick() -> u64;
+    // Temps cumulé des transferts SPI depuis le boot, en µs
+    pub fn hal_bmlite_spi_time_get() -> u64;
+    // Horloge esp_timer en µs depuis le boot (esp_timer.h)
+    pub fn esp_timer_get_time() -> i64;
+
+    // CRC32 utilisé par le lien HCP (fpc_crc.h)
+    pub fn fpc_crc(crc: u32, buf: *const c_void, size: u32) -> u32;
+
+    // RNG matériel ESP-IDF (esp_random.h), utilisé pour les nonces
+    pub fn esp_fill_random(buf: *mut c_void, len: usize);
+}
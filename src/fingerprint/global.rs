@@ -0,0 +1,657 @@
+// Glue std : singleton global protégé par un Mutex, et API libre historique.
+//
+// Les métadonnées en lecture seule (`snapshot`, et les IDs enrôlés avec leurs labels
+// pour `enrolled_ids`, `is_user_enrolled`, `is_single_user` et `list_templates`) sont
+// servies depuis un RwLock séparé, rafraîchi à la fin de chaque opération : un tableau
+// de bord ne reste pas bloqué derrière une identification en cours, tandis que les
+// transactions capteur restent sérialisées par le Mutex. Ces valeurs datent de la fin
+// de la dernière opération terminée et viennent du cache hôte (voir
+// `SensorCtx::refresh_cache`) : une modification faite hors de ce module n'y apparaît
+// qu'après `refresh_cache`. Cache inconnu ou capteur non initialisé : la requête
+// passe par le verrou capteur, comme avant.
+
+use anyhow::Result;
+use lazy_static::lazy_static;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Mutex, RwLock};
+
+use super::continuous::IrqMaskGuard;
+#[cfg(feature = "crypto")]
+use super::SignedMatch;
+use super::{
+    BenchReport, CancelToken, Clock, ConnectStatus, ControlLineState, CoverageCallback,
+    DiagnosticsReport, HcpArgView, HealthEstimate, IdentifyPolicy, ImageGeometry, InitReport,
+    LinkQuality, LockoutPolicy, MatchCallback, MatchGeometry, NoMatchReason, OpTiming, PinConfig,
+    Profile, RemovalReport, RepeatabilityScore, SecurityProfile, SecurityState, SensorCtx,
+    SensorLock, SensorSnapshot, SpiHost, TemplateStats, HARDWARE_ID_LEN,
+};
+#[cfg(feature = "enroll")]
+use super::{CalibrationEvent, EnrollPreparation, EnrollReport, ExistingPolicy};
+
+impl SensorLock for Mutex<SensorCtx> {
+    fn with_ctx<R>(&self, f: impl FnOnce(&mut SensorCtx) -> R) -> R {
+        f(&mut self.lock().unwrap())
+    }
+}
+
+// État publié à la fin de chaque opération, lisible sans le verrou capteur
+struct Published {
+    snapshot: SensorSnapshot,
+    // IDs enrôlés et labels, `None` si inconnus (voir `SensorCtx::cached_templates`)
+    templates: Option<Vec<(u16, Option<String>)>>,
+}
+
+impl Published {
+    fn of(ctx: &SensorCtx) -> Self {
+        Self {
+            snapshot: ctx.snapshot(),
+            templates: ctx.cached_templates(),
+        }
+    }
+}
+
+lazy_static! {
+    static ref SENSOR_CTX: Mutex<SensorCtx> = Mutex::new(SensorCtx::new());
+    static ref PUBLISHED: RwLock<Published> = RwLock::new(Published::of(&SensorCtx::new()));
+}
+
+// Exécute une opération sous le verrou capteur puis publie le nouvel état
+pub(super) fn with_sensor<R>(f: impl FnOnce(&mut SensorCtx) -> R) -> R {
+    SENSOR_CTX.with_ctx(|ctx| {
+        let r = f(ctx);
+        #[cfg(feature = "nvs")]
+        ctx.flush_nvs_if_due();
+        *PUBLISHED.write().unwrap() = Published::of(ctx);
+        r
+    })
+}
+
+// Réponse depuis les templates publiés, sinon `fallback` sous le verrou capteur
+fn with_templates<R>(
+    f: impl FnOnce(&[(u16, Option<String>)]) -> R,
+    fallback: impl FnOnce(&mut SensorCtx) -> Result<R>,
+) -> Result<R> {
+    // Garde de lecture relâchée avant un éventuel `with_sensor`, qui publie
+    let published = PUBLISHED.read().unwrap().templates.as_deref().map(f);
+    match published {
+        Some(r) => Ok(r),
+        None => with_sensor(fallback),
+    }
+}
+
+// `with_sensor` pour une opération exclusive : détection de fond masquée avant
+// d'attendre le verrou, jusqu'à la fin de l'opération (voir `mask_irq`)
+fn with_sensor_exclusive<R>(f: impl FnOnce(&mut SensorCtx) -> R) -> R {
+    let _mask = IrqMaskGuard::new();
+    with_sensor(f)
+}
+
+/// Métadonnées en cache (nombre de templates, dernier match, santé), sans attendre
+/// l'opération capteur en cours. Voir `SensorSnapshot` pour la fraîcheur des valeurs.
+pub fn snapshot() -> SensorSnapshot {
+    PUBLISHED.read().unwrap().snapshot
+}
+
+pub fn init() -> Result<ConnectStatus> {
+    with_sensor(|ctx| ctx.init())
+}
+
+/// Comme `init`, avec un autre câblage que celui de l'ESP32-S3 DevKit.
+pub fn init_with_pins(pins: &PinConfig) -> Result<ConnectStatus> {
+    with_sensor(|ctx| ctx.init_with_pins(pins))
+}
+
+/// Comme `init_with_pins`, avec le résumé du capteur (voir `SensorCtx::init_with_report`).
+pub fn init_with_report(pins: &PinConfig) -> Result<InitReport> {
+    with_sensor(|ctx| ctx.init_with_report(pins))
+}
+
+/// Comme `init`, mais avec une target `log` personnalisée (défaut : `DEFAULT_LOG_TARGET`).
+pub fn init_with_log_target(target: &'static str) -> Result<ConnectStatus> {
+    with_sensor(|ctx| {
+        ctx.set_log_target(target);
+        ctx.init()
+    })
+}
+
+/// Libère SPI, GPIO et buffers ; `NotInitialized` si le capteur ne l'était pas.
+pub fn deinit() -> Result<()> {
+    with_sensor(|ctx| ctx.deinit())
+}
+
+/// Met le capteur en veille (tenu en reset) sans libérer SPI / GPIO (voir `SensorCtx::suspend`).
+pub fn suspend() -> Result<()> {
+    with_sensor(|ctx| ctx.suspend())
+}
+
+/// Sort de veille et vérifie l'identité du capteur.
+pub fn resume() -> Result<()> {
+    with_sensor(|ctx| ctx.resume())
+}
+
+/// Réinitialise la couche HCP seule et vérifie le lien (voir `SensorCtx::reset_hcp`).
+pub fn reset_hcp() -> Result<()> {
+    with_sensor(|ctx| ctx.reset_hcp())
+}
+
+/// Reset matériel du BM-Lite puis vérification qu'il s'agit toujours du même capteur.
+/// Sur `SensorChanged`, l'appelant doit resynchroniser tout état mis en cache (templates, etc.).
+pub fn reconnect() -> Result<ConnectStatus> {
+    with_sensor(|ctx| ctx.reconnect())
+}
+
+/// Seuil de récupération automatique du lien, `0` pour la désactiver (voir
+/// `SensorCtx::set_auto_recovery`).
+pub fn set_auto_recovery(threshold: u8) -> Result<()> {
+    with_sensor(|ctx| ctx.set_auto_recovery(threshold))
+}
+
+/// Échecs de communication consécutifs, lus depuis le snapshot.
+pub fn comms_failures() -> u8 {
+    snapshot().comms_failures
+}
+
+/// Init avec le premier câblage de `presets` où le capteur répond (voir
+/// `SensorCtx::auto_detect`).
+pub fn auto_detect(presets: &[PinConfig]) -> Result<PinConfig> {
+    with_sensor(|ctx| ctx.auto_detect(presets))
+}
+
+/// Déplace le capteur sur un autre contrôleur SPI (voir `SensorCtx::move_spi_host`).
+pub fn move_spi_host(host: SpiHost) -> Result<ConnectStatus> {
+    with_sensor(|ctx| ctx.move_spi_host(host))
+}
+
+pub fn hardware_id() -> Result<[u8; HARDWARE_ID_LEN]> {
+    with_sensor(|ctx| ctx.hardware_id())
+}
+
+#[cfg(feature = "enroll")]
+pub fn calibrate() -> Result<()> {
+    with_sensor_exclusive(|ctx| ctx.calibrate())
+}
+
+/// Calibration avec événements de début / fin (voir `SensorCtx::calibrate_with_progress`).
+#[cfg(feature = "enroll")]
+pub fn calibrate_with_progress(progress: impl FnMut(CalibrationEvent)) -> Result<()> {
+    with_sensor_exclusive(|ctx| ctx.calibrate_with_progress(progress))
+}
+
+/// Tendance des calibrations (durée, échecs) : Healthy / Degraded / Failing.
+pub fn sensor_health() -> HealthEstimate {
+    snapshot().health
+}
+
+/// Rapport de support en un appel (voir `SensorCtx::diagnostics`).
+pub fn diagnostics() -> DiagnosticsReport {
+    with_sensor(|ctx| ctx.diagnostics())
+}
+
+/// Niveaux des GPIO IRQ et RST, sans échange avec le capteur (voir
+/// `SensorCtx::read_control_lines`).
+pub fn read_control_lines() -> Result<ControlLineState> {
+    with_sensor(|ctx| ctx.read_control_lines())
+}
+
+/// Cause du dernier non-match (voir `SensorCtx::last_no_match_reason`), lue depuis le
+/// snapshot.
+pub fn last_no_match_reason() -> Option<NoMatchReason> {
+    snapshot().last_no_match
+}
+
+/// Position du dernier match (voir `SensorCtx::last_match_geometry`), lue depuis le
+/// snapshot.
+pub fn last_match_geometry() -> Option<MatchGeometry> {
+    snapshot().last_match_geometry
+}
+
+/// Température du capteur, `None` si le firmware ne la rapporte pas (voir `SensorCtx::temperature`).
+pub fn temperature() -> Result<Option<f32>> {
+    with_sensor(|ctx| ctx.temperature())
+}
+
+/// Géométrie des images (`Unsupported` sur BM-Lite, voir `SensorCtx::image_geometry`).
+pub fn image_geometry() -> Result<ImageGeometry> {
+    with_sensor(|ctx| ctx.image_geometry())
+}
+
+/// Verrouille la configuration (voir `SensorCtx::lock_config`).
+pub fn lock_config(token: u64) -> Result<()> {
+    with_sensor(|ctx| ctx.lock_config(token))
+}
+
+pub fn unlock_config(token: u64) -> Result<()> {
+    with_sensor(|ctx| ctx.unlock_config(token))
+}
+
+/// État lu sous le verrou : attend la fin d'une opération en cours, mais ne peut pas
+/// être périmé (`snapshot().initialized` pour une lecture sans attente).
+pub fn is_initialized() -> bool {
+    with_sensor(|ctx| ctx.is_initialized())
+}
+
+pub fn is_config_locked() -> bool {
+    snapshot().config_locked
+}
+
+/// Écrit tout de suite les métadonnées en NVS si elles ont changé (voir `SensorCtx::flush_nvs`).
+#[cfg(feature = "nvs")]
+pub fn flush_nvs() -> Result<bool> {
+    with_sensor(|ctx| ctx.flush_nvs())
+}
+
+/// Taux d'échanges SPI en échec sur `iterations` paquets de test (voir `SensorCtx::probe_link`).
+pub fn probe_link(iterations: u32) -> Result<LinkQuality> {
+    with_sensor(|ctx| ctx.probe_link(iterations))
+}
+
+/// Trace hexadécimale des paquets HCP (voir `SensorCtx::set_packet_trace`).
+pub fn set_packet_trace(enabled: bool) {
+    with_sensor(|ctx| ctx.set_packet_trace(enabled))
+}
+
+pub fn enrolled_count() -> Result<u16> {
+    with_sensor(|ctx| ctx.enrolled_count())
+}
+
+/// IDs enrôlés depuis le cache hôte (voir `SensorCtx::refresh_cache`), sans attendre
+/// l'opération capteur en cours (voir l'en-tête du module pour leur fraîcheur).
+pub fn enrolled_ids() -> Result<Vec<u16>> {
+    with_templates(
+        |templates| templates.iter().map(|&(id, _)| id).collect(),
+        |ctx| ctx.enrolled_ids(),
+    )
+}
+
+/// Resynchronise le cache des IDs enrôlés depuis le capteur.
+pub fn refresh_cache() -> Result<Vec<u16>> {
+    with_sensor(|ctx| ctx.refresh_cache())
+}
+
+pub fn max_template_capacity() -> Result<u16> {
+    with_sensor(|ctx| ctx.max_template_capacity())
+}
+
+pub fn free_slots() -> Result<Vec<u16>> {
+    with_sensor(|ctx| ctx.free_slots())
+}
+
+/// Depuis le cache hôte, sans attendre l'opération capteur en cours (voir `enrolled_ids`).
+pub fn is_user_enrolled() -> Result<bool> {
+    with_templates(
+        |templates| !templates.is_empty(),
+        |ctx| ctx.is_user_enrolled(),
+    )
+}
+
+/// Depuis le cache hôte, sans attendre l'opération capteur en cours (voir `enrolled_ids`).
+pub fn is_single_user() -> Result<bool> {
+    with_templates(|templates| templates.len() == 1, |ctx| ctx.is_single_user())
+}
+
+/// Supprime tous les templates, sauf les épinglés si `force` est faux.
+pub fn wipe_templates(force: bool) -> Result<RemovalReport> {
+    with_sensor(|ctx| ctx.wipe_templates(force))
+}
+
+/// Supprime les templates occupés dont l'ID est dans `[start, end]` (bornes incluses).
+/// Les slots vides sont ignorés, les épinglés aussi sauf `force`.
+pub fn remove_templates_in_range(start: u16, end: u16, force: bool) -> Result<RemovalReport> {
+    with_sensor(|ctx| ctx.remove_templates_in_range(start, end, force))
+}
+
+/// Protège le template `id` de `wipe_templates` / `remove_templates_in_range`.
+pub fn pin_template(id: u16) -> Result<()> {
+    with_sensor(|ctx| ctx.pin_template(id))
+}
+
+pub fn unpin_template(id: u16) -> Result<()> {
+    with_sensor(|ctx| ctx.unpin_template(id))
+}
+
+/// IDs épinglés, triés.
+pub fn pinned_templates() -> Vec<u16> {
+    with_sensor(|ctx| ctx.pinned_templates())
+}
+
+/// Qualité (0 à 100) mémorisée à l'enrôlement du template `id` (voir `SensorCtx::template_quality`).
+pub fn template_quality(id: u16) -> Option<u16> {
+    with_sensor(|ctx| ctx.template_quality(id))
+}
+
+/// Restaure une qualité sauvegardée par l'application.
+pub fn set_template_quality(id: u16, quality: u16) {
+    with_sensor(|ctx| ctx.set_template_quality(id, quality))
+}
+
+/// Qualités connues, triées par ID.
+pub fn template_qualities() -> Vec<(u16, u16)> {
+    with_sensor(|ctx| ctx.template_qualities())
+}
+
+/// Répétabilité du doigt avant enrôlement (voir `SensorCtx::assess_finger`).
+pub fn assess_finger(timeout_ms: u32) -> Result<RepeatabilityScore> {
+    with_sensor_exclusive(|ctx| ctx.assess_finger(timeout_ms))
+}
+
+/// Usage du template `id` : enrôlement, dernier match (voir `SensorCtx::template_stats`).
+pub fn template_stats(id: u16) -> Option<TemplateStats> {
+    with_sensor(|ctx| ctx.template_stats(id))
+}
+
+/// Restaure des statistiques sauvegardées par l'application.
+pub fn set_template_stats(id: u16, stats: TemplateStats) {
+    with_sensor(|ctx| ctx.set_template_stats(id, stats))
+}
+
+/// Statistiques connues, triées par ID.
+pub fn all_template_stats() -> Vec<(u16, TemplateStats)> {
+    with_sensor(|ctx| ctx.all_template_stats())
+}
+
+/// Réglages de sécurité en vigueur et FAR effectif estimé (voir `SecurityProfile`).
+pub fn security_profile() -> Result<SecurityProfile> {
+    with_sensor(|ctx| ctx.security_profile())
+}
+
+/// Verrouillage après des non-matchs répétés (voir `LockoutPolicy`).
+pub fn set_lockout_policy(policy: LockoutPolicy) -> Result<()> {
+    with_sensor(|ctx| ctx.set_lockout_policy(policy))
+}
+
+/// Série de non-matchs et verrouillage en cours.
+pub fn security_state() -> SecurityState {
+    with_sensor(|ctx| ctx.security_state())
+}
+
+/// Identifie le doigt posé contre un template fourni par l'appelant, sans l'enregistrer.
+pub fn verify_transient(template: &[u8], timeout_ms: u32) -> Result<bool> {
+    with_sensor(|ctx| ctx.verify_transient(template, timeout_ms))
+}
+
+/// Définit le groupe `group_id` (voir `SensorCtx::verify_group`).
+pub fn set_group(group_id: u16, members: &[u16]) -> Result<()> {
+    with_sensor(|ctx| ctx.set_group(group_id, members))
+}
+
+pub fn remove_group(group_id: u16) -> Result<()> {
+    with_sensor(|ctx| ctx.remove_group(group_id))
+}
+
+pub fn template_groups() -> Vec<(u16, Vec<u16>)> {
+    with_sensor(|ctx| ctx.template_groups())
+}
+
+/// Exige tous les doigts du groupe dans la fenêtre `timeout_ms` (voir `SensorCtx::verify_group`).
+pub fn verify_group(group_id: u16, timeout_ms: u32) -> Result<bool> {
+    with_sensor(|ctx| ctx.verify_group(group_id, timeout_ms))
+}
+
+/// Banc de comparaison avec des templates de référence (voir `SensorCtx::benchmark_against`).
+pub fn benchmark_against(reference_templates: &[Vec<u8>]) -> Result<BenchReport> {
+    with_sensor(|ctx| ctx.benchmark_against(reference_templates))
+}
+
+/// IDs des templates stockés illisibles ou altérés (voir `SensorCtx::verify_store_integrity`).
+pub fn verify_store_integrity() -> Result<Vec<u16>> {
+    with_sensor(|ctx| ctx.verify_store_integrity())
+}
+
+/// Références CRC32 des templates stockés (voir `SensorCtx::record_checksums`).
+pub fn record_checksums() -> Result<Vec<(u16, u32)>> {
+    with_sensor(|ctx| ctx.record_checksums())
+}
+
+pub fn set_template_checksum(id: u16, crc: u32) {
+    with_sensor(|ctx| ctx.set_template_checksum(id, crc))
+}
+
+/// Copie le template `id` (voir `SensorCtx::export_template`).
+pub fn export_template(id: u16) -> Result<Vec<u8>> {
+    with_sensor_exclusive(|ctx| ctx.export_template(id))
+}
+
+/// Copie le template `id` dans `buf`, sans allocation ; renvoie sa taille.
+pub fn export_template_into(id: u16, buf: &mut [u8]) -> Result<usize> {
+    with_sensor_exclusive(|ctx| ctx.export_template_into(id, buf))
+}
+
+/// CRC32 du template `id` exporté : stable pour un même template, pas entre firmwares.
+pub fn template_checksum(id: u16) -> Result<u32> {
+    with_sensor(|ctx| ctx.template_checksum(id))
+}
+
+/// Nombre de mauvais échantillons tolérés avant d'abandonner avec `LowQuality`.
+#[cfg(feature = "enroll")]
+pub fn set_enroll_retry(max_bad_samples: u8) -> Result<()> {
+    with_sensor(|ctx| ctx.set_enroll_retry(max_bad_samples))
+}
+
+/// Retrait du doigt entre deux échantillons (voir `SensorCtx::set_require_lift`).
+#[cfg(feature = "enroll")]
+pub fn set_require_lift(require: bool) -> Result<()> {
+    with_sensor(|ctx| ctx.set_require_lift(require))
+}
+
+#[cfg(feature = "crypto")]
+pub fn export_template_encrypted(id: u16, key: &[u8; 32]) -> Result<Vec<u8>> {
+    with_sensor_exclusive(|ctx| ctx.export_template_encrypted(id, key))
+}
+
+#[cfg(all(feature = "crypto", feature = "enroll"))]
+pub fn import_template_encrypted(id: u16, blob: &[u8], key: &[u8; 32]) -> Result<()> {
+    with_sensor_exclusive(|ctx| ctx.import_template_encrypted(id, blob, key))
+}
+
+/// Identification dont le match est signé avec `key` et lié à `nonce`
+/// (voir `SensorCtx::check_once_signed`).
+#[cfg(feature = "crypto")]
+pub fn check_once_signed(timeout_ms: u32, nonce: &[u8], key: &[u8]) -> Result<Option<SignedMatch>> {
+    with_sensor(|ctx| ctx.check_once_signed(timeout_ms, nonce, key))
+}
+
+/// Calibration automatique sur `NotCalibrated` (voir `SensorCtx::set_auto_calibrate`).
+#[cfg(feature = "enroll")]
+pub fn set_auto_calibrate(enabled: bool) -> Result<()> {
+    with_sensor(|ctx| ctx.set_auto_calibrate(enabled))
+}
+
+/// Couverture minimale des échantillons d'enrôlement (`Unsupported` sur BM-Lite).
+#[cfg(feature = "enroll")]
+pub fn set_enroll_coverage(pct: u8) -> Result<()> {
+    with_sensor(|ctx| ctx.set_enroll_coverage(pct))
+}
+
+/// Orientations enrôlées par doigt (seulement 1 sur BM-Lite).
+#[cfg(feature = "enroll")]
+pub fn set_enroll_orientations(n: u8) -> Result<()> {
+    with_sensor(|ctx| ctx.set_enroll_orientations(n))
+}
+
+/// Zones distinctes du doigt exigées à l'enrôlement (aucune contrainte possible sur
+/// BM-Lite, voir `SensorCtx::set_min_distinct_areas`).
+#[cfg(feature = "enroll")]
+pub fn set_min_distinct_areas(n: u8) -> Result<()> {
+    with_sensor(|ctx| ctx.set_min_distinct_areas(n))
+}
+
+#[cfg(feature = "enroll")]
+pub fn set_calibrate_on_enroll(enabled: bool) -> Result<()> {
+    with_sensor(|ctx| ctx.set_calibrate_on_enroll(enabled))
+}
+
+/// Calibration / reset avant chaque enrôlement (voir `EnrollPreparation`).
+#[cfg(feature = "enroll")]
+pub fn set_enroll_preparation(preparation: EnrollPreparation) -> Result<()> {
+    with_sensor(|ctx| ctx.set_enroll_preparation(preparation))
+}
+
+#[cfg(feature = "enroll")]
+pub fn update_template(id: u16, timeout_ms: u32) -> Result<EnrollReport> {
+    with_sensor_exclusive(|ctx| ctx.update_template(id, timeout_ms))
+}
+
+/// Renforcement d'un template existant (`Unsupported` sur BM-Lite, voir
+/// `SensorCtx::augment_template`).
+#[cfg(feature = "enroll")]
+pub fn augment_template(id: u16, timeout_ms: u32) -> Result<EnrollReport> {
+    with_sensor_exclusive(|ctx| ctx.augment_template(id, timeout_ms))
+}
+
+/// Horloge du contexte, par ex. `MockClock` en test (voir `SensorCtx::set_clock`).
+pub fn set_clock(clock: impl Clock + 'static) -> Result<()> {
+    with_sensor(|ctx| ctx.set_clock(clock))
+}
+
+/// Miroir hôte des templates (voir `SensorCtx::enable_mirror`).
+pub fn enable_mirror(enabled: bool) -> Result<()> {
+    with_sensor_exclusive(|ctx| ctx.enable_mirror(enabled))
+}
+
+/// Tous les templates (et leurs labels) dans un blob de sauvegarde versionné.
+pub fn export_all() -> Result<Vec<u8>> {
+    with_sensor_exclusive(|ctx| ctx.export_all())
+}
+
+#[cfg(feature = "enroll")]
+pub fn import_all(blob: &[u8]) -> Result<u16> {
+    with_sensor_exclusive(|ctx| ctx.import_all(blob))
+}
+
+pub fn set_label(id: u16, label: &str) {
+    with_sensor(|ctx| ctx.set_label(id, label))
+}
+
+pub fn clear_label(id: u16) {
+    with_sensor(|ctx| ctx.clear_label(id))
+}
+
+pub fn label(id: u16) -> Option<String> {
+    with_sensor(|ctx| ctx.label(id))
+}
+
+/// Templates et labels depuis le cache hôte, sans attendre l'opération capteur en
+/// cours ni relire le capteur (voir `enrolled_ids`) ; `refresh_cache` avant pour
+/// voir une modification faite hors de ce module.
+pub fn list_templates() -> Result<Vec<(u16, Option<String>)>> {
+    with_templates(<[_]>::to_vec, |ctx| ctx.list_templates())
+}
+
+#[cfg(feature = "enroll")]
+pub fn enroll_and_verify(id: u16, timeout_ms: u32) -> Result<EnrollReport> {
+    with_sensor_exclusive(|ctx| ctx.enroll_and_verify(id, timeout_ms))
+}
+
+#[cfg(feature = "enroll")]
+pub fn enroll_next() -> Result<u16> {
+    with_sensor_exclusive(|ctx| ctx.enroll_next())
+}
+
+#[cfg(feature = "enroll")]
+pub fn enroll_user() -> Result<EnrollReport> {
+    with_sensor_exclusive(|ctx| ctx.enroll_user())
+}
+
+/// Enrôle sous l'ID 1 si le capteur est vide, sinon selon `policy` (défaut : `Skip`).
+#[cfg(feature = "enroll")]
+pub fn enroll_user_if_needed(policy: ExistingPolicy) -> Result<Option<EnrollReport>> {
+    with_sensor_exclusive(|ctx| ctx.enroll_user_if_needed(policy))
+}
+
+/// Voir `SensorCtx::read_arg` : tout appel intercalé depuis l'opération en écrase
+/// les arguments.
+pub fn read_arg() -> Result<HcpArgView> {
+    with_sensor(|ctx| ctx.read_arg())
+}
+
+/// Attend qu'un doigt soit posé. Retourne `false` si le délai expire.
+pub fn wait_for_finger(timeout_ms: u32) -> Result<bool> {
+    with_sensor(|ctx| ctx.wait_for_finger(timeout_ms))
+}
+
+/// Attend que le doigt soit retiré. Retourne `false` si le doigt reste posé.
+/// Le verrou capteur est tenu pendant l'attente : annuler via le `CancelToken`
+/// (cloné dans l'autre tâche), pas en appelant une autre fonction du module.
+pub fn wait_for_finger_cancellable(timeout_ms: u32, cancel: &CancelToken) -> Result<bool> {
+    with_sensor(|ctx| ctx.wait_for_finger_cancellable(timeout_ms, cancel))
+}
+
+pub fn wait_for_finger_release(timeout_ms: u32) -> Result<bool> {
+    with_sensor(|ctx| ctx.wait_for_finger_release(timeout_ms))
+}
+
+pub fn arm() {
+    with_sensor(|ctx| ctx.arm())
+}
+
+pub fn disarm() {
+    with_sensor(|ctx| ctx.disarm())
+}
+
+/// Sensibilité de détection du doigt (voir `SensorCtx::set_detect_sensitivity`).
+pub fn set_detect_sensitivity(level: u8) -> Result<()> {
+    with_sensor(|ctx| ctx.set_detect_sensitivity(level))
+}
+
+/// État lu depuis le snapshot : ne bloque pas derrière une identification en cours.
+pub fn is_armed() -> bool {
+    snapshot().armed
+}
+
+pub fn set_identify_policy(policy: IdentifyPolicy) -> Result<()> {
+    with_sensor(|ctx| ctx.set_identify_policy(policy))
+}
+
+/// Nouvelles poses du doigt après un non-match (voir `SensorCtx::set_auto_reattempt`).
+pub fn set_auto_reattempt(count: u8, window_ms: u32) -> Result<()> {
+    with_sensor(|ctx| ctx.set_auto_reattempt(count, window_ms))
+}
+
+/// Réglages hôte d'un profil d'installation (voir `Profile`).
+pub fn apply_profile(profile: Profile) -> Result<()> {
+    with_sensor(|ctx| ctx.apply_profile(profile))
+}
+
+/// Action appelée à chaque match, sous le verrou global : ne pas rappeler
+/// `fingerprint::*` depuis le callback (voir `SensorCtx::on_match`).
+pub fn on_match(callback: MatchCallback) {
+    with_sensor(|ctx| ctx.on_match(callback))
+}
+
+pub fn clear_on_match() {
+    with_sensor(|ctx| ctx.clear_on_match())
+}
+
+/// Couverture du doigt pendant la capture : non fournie par le BM-Lite (voir
+/// `SensorCtx::on_coverage`).
+pub fn on_coverage(callback: CoverageCallback) -> Result<()> {
+    with_sensor(|ctx| ctx.on_coverage(callback))
+}
+
+/// Durées SPI / capteur de la dernière opération (voir `SensorCtx::last_timing`).
+pub fn last_timing() -> Option<OpTiming> {
+    with_sensor(|ctx| ctx.last_timing())
+}
+
+/// Voir `SensorCtx::check_once` : `0` = tentative unique non bloquante, timeout
+/// plafonné à `MAX_IDENTIFY_TIMEOUT_MS`.
+pub fn check_once(timeout_ms: u32) -> Result<bool> {
+    with_sensor(|ctx| ctx.check_once(timeout_ms))
+}
+
+/// Voir `SensorCtx::check_once_cancellable` pour la granularité d'annulation.
+pub fn check_once_cancellable(timeout_ms: u32, cancel: &CancelToken) -> Result<bool> {
+    with_sensor(|ctx| ctx.check_once_cancellable(timeout_ms, cancel))
+}
+
+/// Attend un doigt autorisé (voir `SensorCtx::identify_until_match`). Le verrou capteur
+/// est tenu jusqu'au match ou à `stop` : les autres appels attendent, sauf `snapshot`.
+/// Pour un scan qui laisse l'API libre, voir `start_continuous`.
+pub fn identify_until_match(stop: &AtomicBool) -> Result<u16> {
+    with_sensor(|ctx| ctx.identify_until_match(stop))
+}
+
+/// Voir `SensorCtx::identify_candidates` : au plus un candidat, score non fourni par le firmware.
+pub fn identify_candidates(n: usize, timeout_ms: u32) -> Result<Vec<(u16, u16)>> {
+    with_sensor(|ctx| ctx.identify_candidates(n, timeout_ms))
+}
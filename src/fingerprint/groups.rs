@@ -0,0 +1,106 @@
+#![forbid(unsafe_code)]
+
+// Groupes de templates pour une authentification multi-doigts : chaque doigt est
+// enrôlé sous son propre ID, le groupe les associe et `verify_group` exige de les
+// voir tous passer dans une même fenêtre de temps.
+//
+// Le BM-Lite ne connaît que des templates isolés : les groupes sont tenus côté
+// hôte et persistés comme les labels (feature `nvs`, sinon via `template_groups`).
+
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use anyhow::{anyhow, Result};
+
+use super::error::FingerprintError;
+use super::sensor::{not_initialized, SensorCtx, MAX_IDENTIFY_TIMEOUT_MS};
+
+impl SensorCtx {
+    /// Définit (ou remplace) le groupe `group_id` avec les templates `members`
+    /// (doublons ignorés). Aucun accès capteur : les IDs peuvent être enrôlés après.
+    pub fn set_group(&mut self, group_id: u16, members: &[u16]) -> Result<()> {
+        self.check_unlocked("set_group")?;
+        let members: BTreeSet<u16> = members.iter().copied().collect();
+        if members.is_empty() {
+            return Err(anyhow!("empty template group {group_id}"));
+        }
+        self.groups.insert(group_id, members.into_iter().collect());
+        Ok(())
+    }
+
+    pub fn remove_group(&mut self, group_id: u16) -> Result<()> {
+        self.check_unlocked("remove_group")?;
+        self.groups.remove(&group_id);
+        Ok(())
+    }
+
+    /// Templates du groupe `group_id`, triés.
+    pub fn group_members(&self, group_id: u16) -> Option<Vec<u16>> {
+        self.groups.get(&group_id).cloned()
+    }
+
+    /// Tous les groupes définis.
+    pub fn template_groups(&self) -> Vec<(u16, Vec<u16>)> {
+        self.groups
+            .iter()
+            .map(|(&id, members)| (id, members.clone()))
+            .collect()
+    }
+
+    /// Demande chaque doigt du groupe, un par un et dans n'importe quel ordre, et
+    /// réussit quand tous ont été reconnus avant la fin de `timeout_ms` (fenêtre
+    /// totale, pas par doigt). Représenter un doigt déjà reconnu ne compte pas.
+    ///
+    /// `Ok(false)` dès qu'un doigt hors du groupe (ou inconnu) est posé, ou si la
+    /// fenêtre expire. `on_match` n'est pas appelé : un doigt seul n'ouvre rien.
+    pub fn verify_group(&mut self, group_id: u16, timeout_ms: u32) -> Result<bool> {
+        if self.chain.is_none() {
+            return Err(not_initialized());
+        }
+        let mut remaining: BTreeSet<u16> = self
+            .groups
+            .get(&group_id)
+            .ok_or_else(|| anyhow!("unknown template group {group_id}"))?
+            .iter()
+            .copied()
+            .collect();
+        if !self.armed {
+            return Ok(false);
+        }
+        self.check_lockout()?;
+
+        let deadline = self.clock.now_ms() + u64::from(timeout_ms);
+        while !remaining.is_empty() {
+            let left = deadline.saturating_sub(self.clock.now_ms());
+            if left == 0 {
+                break;
+            }
+            let timeout = (left as u32).min(MAX_IDENTIFY_TIMEOUT_MS);
+            match self.identify_attempt(timeout, None) {
+                Ok(Some(id)) if remaining.remove(&id) => {
+                    fp_info!(target: self.log_target, "Groupe {group_id}: template {id} reconnu, {} restant(s)", remaining.len());
+                }
+                Ok(Some(id)) if self.groups[&group_id].contains(&id) => {}
+                Ok(_) => {
+                    fp_warn!(target: self.log_target, "Groupe {group_id}: doigt hors du groupe");
+                    self.record_access(false);
+                    return Ok(false);
+                }
+                Err(e)
+                    if matches!(FingerprintError::of(&e), Some(FingerprintError::Timeout(_))) =>
+                {
+                    break
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        if !remaining.is_empty() {
+            fp_warn!(target: self.log_target, "Groupe {group_id}: fenêtre expirée, manquent {remaining:?}");
+        }
+        // Fenêtre expirée : doigts manquants, pas un refus
+        if remaining.is_empty() {
+            self.record_access(true);
+        }
+        Ok(remaining.is_empty())
+    }
+}
@@ -0,0 +1,102 @@
+#![forbid(unsafe_code)]
+
+// Suivi des calibrations successives pour estimer l'usure / encrassement du capteur.
+//
+// Le firmware BM-Lite ne renvoie aucune valeur de calibration (seulement un code
+// résultat) : la dérive est donc estimée à partir de la durée de calibration et des
+// échecs. La baseline est la première calibration réussie depuis le boot (non persistée).
+
+// Dérive de durée (en %) au-delà de laquelle le capteur est considéré dégradé
+const DEGRADED_DRIFT_PCT: i32 = 50;
+// Nombre d'échecs consécutifs au-delà duquel le capteur est considéré défaillant
+const FAILING_CONSECUTIVE: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum HealthStatus {
+    /// Aucune calibration effectuée depuis le boot
+    Unknown,
+    Healthy,
+    Degraded,
+    Failing,
+}
+
+/// Résumé de la santé du capteur à partir de l'historique de `calibrate()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HealthEstimate {
+    pub status: HealthStatus,
+    pub calibrations: u32,
+    pub failures: u32,
+    pub consecutive_failures: u32,
+    /// Durée de la première calibration réussie (ms)
+    pub baseline_ms: Option<u32>,
+    /// Durée de la dernière calibration réussie (ms)
+    pub last_ms: Option<u32>,
+    /// Écart de `last_ms` par rapport à `baseline_ms`, en %
+    pub drift_pct: Option<i32>,
+}
+
+pub(super) struct CalibrationTracker {
+    calibrations: u32,
+    failures: u32,
+    consecutive_failures: u32,
+    baseline_ms: Option<u32>,
+    last_ms: Option<u32>,
+}
+
+impl CalibrationTracker {
+    pub const fn new() -> Self {
+        Self {
+            calibrations: 0,
+            failures: 0,
+            consecutive_failures: 0,
+            baseline_ms: None,
+            last_ms: None,
+        }
+    }
+
+    #[cfg(feature = "enroll")]
+    pub fn record(&mut self, duration_ms: u32, ok: bool) {
+        self.calibrations += 1;
+        if ok {
+            self.consecutive_failures = 0;
+            self.baseline_ms.get_or_insert(duration_ms);
+            self.last_ms = Some(duration_ms);
+        } else {
+            self.failures += 1;
+            self.consecutive_failures += 1;
+        }
+    }
+
+    pub fn estimate(&self) -> HealthEstimate {
+        let drift_pct = match (self.baseline_ms, self.last_ms) {
+            (Some(base), Some(last)) if base > 0 => {
+                Some(((last as i64 - base as i64) * 100 / base as i64) as i32)
+            }
+            _ => None,
+        };
+
+        let status = if self.calibrations == 0 {
+            HealthStatus::Unknown
+        } else if self.consecutive_failures >= FAILING_CONSECUTIVE {
+            HealthStatus::Failing
+        } else if self.consecutive_failures > 0
+            || drift_pct.is_some_and(|d| d.abs() > DEGRADED_DRIFT_PCT)
+        {
+            HealthStatus::Degraded
+        } else {
+            HealthStatus::Healthy
+        };
+
+        HealthEstimate {
+            status,
+            calibrations: self.calibrations,
+            failures: self.failures,
+            consecutive_failures: self.consecutive_failures,
+            baseline_ms: self.baseline_ms,
+            last_ms: self.last_ms,
+            drift_pct,
+        }
+    }
+}
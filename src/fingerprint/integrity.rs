@@ -0,0 +1,133 @@
+#![forbid(unsafe_code)]
+
+// Vérification des templates stockés, pour repérer une corruption flash avant qu'un
+// utilisateur ne soit refusé.
+//
+// Le BM-Lite n'a pas de commande de validation : chaque template est relu
+// (flash -> RAM BM-Lite -> hôte). Le firmware refuse de charger un template illisible,
+// ce qui le désigne comme corrompu. Pour détecter aussi une altération qui reste
+// lisible, le CRC32 relu est comparé à une référence prise par `record_checksums`.
+// Les références font partie des métadonnées hôte (blob NVS avec la feature `nvs`,
+// sinon `set_template_checksum` au boot). Elles sont oubliées
+// dès que le template est réécrit (enrôlement, mise à jour, import).
+//
+// Avant import, `validate_template_bytes` écarte côté hôte ce qui ne peut pas être un
+// template. Le format FPC est opaque (ni en-tête ni version documentés) : seuls la
+// taille et quelques contenus manifestement faux sont vérifiables, la compatibilité
+// avec le firmware ne l'est pas.
+
+use alloc::vec::Vec;
+use anyhow::Result;
+
+use super::backup::MAGIC as BACKUP_MAGIC;
+use super::bep;
+use super::error::{FingerprintError, BEP_RESULT_IO_ERROR};
+use super::sensor::{not_initialized, SensorCtx, MAX_TEMPLATE_LEN};
+use super::table::IdTable;
+
+/// Informations sur un template validé par `validate_template_bytes`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TemplateMeta {
+    /// Taille en octets
+    pub len: usize,
+    /// CRC32 du contenu, à passer à `set_template_checksum` après l'import
+    pub crc32: u32,
+}
+
+/// Vérifie, sans parler au capteur, que `data` peut être un template BM-Lite :
+/// non vide, au plus `MAX_TEMPLATE_LEN` octets, ni flash effacée (octets tous
+/// identiques), ni blob de sauvegarde `export_all`. Sinon
+/// `FingerprintError::IncompatibleTemplate`.
+///
+/// Le format FPC ne porte pas de version lisible côté hôte : un template d'un autre
+/// firmware passe cette vérification et n'est refusé qu'au chargement par le capteur.
+pub fn validate_template_bytes(data: &[u8]) -> Result<TemplateMeta> {
+    let why = match data {
+        [] => Some("empty"),
+        _ if data.len() > MAX_TEMPLATE_LEN => Some("longer than MAX_TEMPLATE_LEN"),
+        _ if data.starts_with(BACKUP_MAGIC) => Some("export_all backup, use import_all"),
+        [first, rest @ ..] if rest.iter().all(|b| b == first) => Some("uniform bytes"),
+        _ => None,
+    };
+    if let Some(why) = why {
+        return Err(FingerprintError::IncompatibleTemplate(why).into());
+    }
+    Ok(TemplateMeta {
+        len: data.len(),
+        crc32: bep::crc32(data),
+    })
+}
+
+impl SensorCtx {
+    /// Relit chaque template stocké et renvoie les IDs en échec : chargement refusé
+    /// par le firmware, ou CRC32 différent de la référence enregistrée. Un template
+    /// sans référence n'est contrôlé que sur sa lecture.
+    ///
+    /// Les erreurs de communication (timeout, contention, lien) interrompent la
+    /// vérification avec une erreur plutôt que de désigner des templates sains.
+    pub fn verify_store_integrity(&mut self) -> Result<Vec<u16>> {
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+        let ids = chain.template_ids()?;
+        self.enrolled.set(&ids);
+
+        let mut failed = Vec::new();
+        for id in ids {
+            let data = match chain.template_export(id) {
+                Ok(data) => data,
+                Err(e) => match FingerprintError::of(&e) {
+                    // Une erreur de trame (-8) met en cause le lien, pas le template
+                    Some(FingerprintError::Bep { code, .. }) if code != BEP_RESULT_IO_ERROR => {
+                        fp_warn!(target: self.log_target, "Template {id}: illisible ({e})");
+                        failed.push(id);
+                        continue;
+                    }
+                    _ => return Err(e),
+                },
+            };
+            let crc = bep::crc32(&data);
+            if self
+                .checksums
+                .get(id)
+                .is_some_and(|expected| expected != crc)
+            {
+                fp_warn!(target: self.log_target, "Template {id}: CRC32 {crc:08x} différent de la référence");
+                failed.push(id);
+            }
+        }
+
+        fp_info!(
+            target: self.log_target,
+            "Vérification des templates : {} en échec {:?}",
+            failed.len(),
+            failed
+        );
+        Ok(failed)
+    }
+
+    /// Prend comme référence le CRC32 actuel de chaque template stocké et renvoie
+    /// les paires `(id, crc)`. Sur erreur, les références
+    /// précédentes sont conservées.
+    pub fn record_checksums(&mut self) -> Result<Vec<(u16, u32)>> {
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+        let ids = chain.template_ids()?;
+        self.enrolled.set(&ids);
+
+        // Remplace la table une fois tous les exports réussis
+        let mut checksums = IdTable::new();
+        for id in ids {
+            let crc = bep::crc32(&chain.template_export(id)?);
+            if !checksums.insert(id, crc) {
+                fp_warn!(target: self.log_target, "Template {id}: CRC32 non mémorisé, table pleine");
+            }
+        }
+        self.checksums = checksums;
+        Ok(self.checksums.iter().collect())
+    }
+
+    /// Restaure une référence sauvegardée (voir `record_checksums`).
+    pub fn set_template_checksum(&mut self, id: u16, crc: u32) {
+        if !self.checksums.insert(id, crc) {
+            fp_warn!(target: self.log_target, "Template {id}: CRC32 ignoré, table pleine");
+        }
+    }
+}
@@ -0,0 +1,79 @@
+#![forbid(unsafe_code)]
+
+// Noms lisibles associés aux IDs de templates. Le BM-Lite ne stocke que des u16 :
+// les labels vivent en RAM côté hôte. Avec la feature `nvs`, ils sont sauvegardés
+// avec les autres métadonnées (voir `nvs.rs`) ; sans elle, l'application les relit
+// avec `list_templates` et les restaure au boot avec `set_label`. Stockés sans allocation (`MAX_LABEL_LEN` octets par ID, au plus
+// `MAX_TRACKED_TEMPLATES` IDs).
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use anyhow::Result;
+
+use super::sensor::{not_initialized, SensorCtx};
+use super::table::MAX_TRACKED_TEMPLATES;
+
+/// Longueur maximale d'un label, en octets UTF-8.
+pub const MAX_LABEL_LEN: usize = 32;
+
+// Label en place, tronqué à la limite d'un caractère
+#[derive(Clone, Copy)]
+pub(super) struct Label {
+    bytes: [u8; MAX_LABEL_LEN],
+    len: u8,
+}
+
+impl Label {
+    pub(super) fn new(label: &str) -> Self {
+        let mut len = label.len().min(MAX_LABEL_LEN);
+        while !label.is_char_boundary(len) {
+            len -= 1;
+        }
+        let mut bytes = [0; MAX_LABEL_LEN];
+        bytes[..len].copy_from_slice(&label.as_bytes()[..len]);
+        Self {
+            bytes,
+            len: len as u8,
+        }
+    }
+
+    pub(super) fn as_str(&self) -> &str {
+        // Toujours valide : copié d'un `&str`, coupé à la limite d'un caractère
+        core::str::from_utf8(&self.bytes[..usize::from(self.len)]).unwrap_or_default()
+    }
+}
+
+impl SensorCtx {
+    /// Associe `label` au template `id` (remplace le précédent), tronqué à
+    /// `MAX_LABEL_LEN` octets. Ignoré, avec un avertissement, si `id` est nouveau et
+    /// que `MAX_TRACKED_TEMPLATES` IDs ont déjà un label.
+    pub fn set_label(&mut self, id: u16, label: &str) {
+        if !self.labels.insert(id, Label::new(label)) {
+            fp_warn!(target: self.log_target, "Template {id}: label ignoré, {MAX_TRACKED_TEMPLATES} IDs déjà suivis");
+        }
+    }
+
+    pub fn clear_label(&mut self, id: u16) {
+        self.labels.remove(id);
+    }
+
+    pub fn label(&self, id: u16) -> Option<String> {
+        self.labels.get(id).map(|label| label.as_str().to_string())
+    }
+
+    /// Templates présents sur le capteur, avec leur label s'il y en a un.
+    pub fn list_templates(&mut self) -> Result<Vec<(u16, Option<String>)>> {
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+        let ids = chain.template_ids()?;
+        self.enrolled.set(&ids);
+        Ok(ids.into_iter().map(|id| (id, self.label(id))).collect())
+    }
+
+    // `list_templates` depuis le cache, sans SPI (publié par le singleton global) ;
+    // `None` si le capteur n'est pas initialisé ou le cache inconnu
+    pub(super) fn cached_templates(&self) -> Option<Vec<(u16, Option<String>)>> {
+        self.chain.as_ref()?;
+        let ids = self.enrolled.ids()?;
+        Some(ids.map(|id| (id, self.label(id))).collect())
+    }
+}
@@ -0,0 +1,52 @@
+#![forbid(unsafe_code)]
+
+// Détection de contention sur un bus SPI partagé.
+//
+// L'arbitrage du bus reste l'affaire du HAL (esp-idf sérialise les transactions d'un
+// même hôte, mais un périphérique mal configuré ou piloté hors driver peut corrompre
+// une trame). Côté module, seul le symptôme est visible : des erreurs de trame
+// (`FPC_BEP_RESULT_IO_ERROR` : CRC, ACK, taille) rapprochées dans le temps.
+//
+// Heuristique : plusieurs erreurs de trame dans une courte fenêtre, alors que le
+// capteur a répondu correctement dans la même fenêtre, indiquent une contention. Un
+// capteur mort (ou débranché) échoue au contraire sans aucune réponse valide
+// intercalée : l'erreur reste un `Bep { code: -8 }` ordinaire.
+
+use super::bep;
+
+// Fenêtre d'observation des erreurs de trame
+const CONTENTION_WINDOW_MS: u64 = 2_000;
+// Erreurs de trame dans la fenêtre à partir desquelles la contention est suspectée
+const CONTENTION_ERRORS: usize = 2;
+
+/// Historique récent du lien HCP (tenu par `Chain`).
+pub(super) struct LinkMonitor {
+    // Instants (ms) des dernières erreurs de trame, la plus récente en dernier
+    errors: [Option<u64>; CONTENTION_ERRORS],
+    last_ok_ms: Option<u64>,
+}
+
+impl LinkMonitor {
+    pub const fn new() -> Self {
+        Self {
+            errors: [None; CONTENTION_ERRORS],
+            last_ok_ms: None,
+        }
+    }
+
+    /// Échange réussi : le capteur a répondu (quel que soit le code métier).
+    pub fn record_ok(&mut self) {
+        self.last_ok_ms = Some(bep::tick_ms());
+    }
+
+    /// Erreur de trame. Renvoie `true` si le motif évoque une contention de bus.
+    pub fn record_error(&mut self) -> bool {
+        let now = bep::tick_ms();
+        self.errors.rotate_left(1);
+        self.errors[CONTENTION_ERRORS - 1] = Some(now);
+
+        let recent =
+            |t: Option<u64>| t.is_some_and(|t| now.saturating_sub(t) <= CONTENTION_WINDOW_MS);
+        self.errors.iter().all(|&t| recent(t)) && recent(self.last_ok_ms)
+    }
+}
@@ -0,0 +1,60 @@
+#![forbid(unsafe_code)]
+
+// Verrouillage de la configuration d'un appareil déployé : une fois `lock_config`
+// appelé, les opérations d'administration renvoient `FingerprintError::Locked` tant
+// que `unlock_config` n'a pas reçu le même jeton.
+//
+// Protection contre un chemin de code bogué, pas contre un attaquant qui exécute du
+// code sur l'ESP : le jeton vit en RAM et le verrou tombe au redémarrage (à reposer
+// au boot après la configuration). Restent permis : identification, lecture,
+// métadonnées hôte (labels, qualités, épinglage), `arm` / `disarm`, `init` (sans
+// changer un câblage déjà initialisé) et les comportements réglés avant le verrou
+// (calibration automatique, ...).
+//
+// Opérations verrouillées : `deinit`, `calibrate*`, enrôlement et mise à jour,
+// suppression et import de templates, réglages d'identification et d'enrôlement,
+// `unpin_template`, groupes.
+
+use anyhow::Result;
+
+use super::error::FingerprintError;
+use super::sensor::SensorCtx;
+
+impl SensorCtx {
+    /// Verrouille la configuration avec `token`. `FingerprintError::Locked` si elle
+    /// l'est déjà (le jeton en place n'est pas remplacé).
+    pub fn lock_config(&mut self, token: u64) -> Result<()> {
+        self.check_unlocked("lock_config")?;
+        self.config_lock = Some(token);
+        fp_info!(target: self.log_target, "Configuration verrouillée");
+        Ok(())
+    }
+
+    /// Déverrouille si `token` est celui de `lock_config`, sinon
+    /// `FingerprintError::Locked`. Sans effet si la configuration n'est pas verrouillée.
+    pub fn unlock_config(&mut self, token: u64) -> Result<()> {
+        match self.config_lock {
+            Some(expected) if expected != token => {
+                fp_warn!(target: self.log_target, "Déverrouillage refusé : jeton incorrect");
+                Err(FingerprintError::Locked("unlock_config").into())
+            }
+            _ => {
+                self.config_lock = None;
+                Ok(())
+            }
+        }
+    }
+
+    pub fn is_config_locked(&self) -> bool {
+        self.config_lock.is_some()
+    }
+
+    // Refuse l'opération `what` tant que la configuration est verrouillée
+    pub(super) fn check_unlocked(&self, what: &'static str) -> Result<()> {
+        if self.config_lock.is_some() {
+            fp_warn!(target: self.log_target, "{what} refusé : configuration verrouillée");
+            return Err(FingerprintError::Locked(what).into());
+        }
+        Ok(())
+    }
+}
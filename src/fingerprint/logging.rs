@@ -0,0 +1,35 @@
+// Journalisation du module : toujours via `log` (EspLogger, env_logger, ...) et, sous
+// la feature `defmt`, aussi via `defmt` (RTT). Les deux sorties sont cumulées : sans
+// logger `log` installé, seule la sortie defmt reste visible.
+//
+// defmt n'accepte pas les format strings de `core::fmt` (arguments nommés, `{:?}`
+// sur des types sans `defmt::Format`) : le message est formaté côté hôte puis envoyé
+// comme `{=str}`, au prix d'une allocation par message. L'application fournit le
+// transport defmt (`defmt-rtt`, ...) et son `#[global_logger]`.
+
+macro_rules! fp_log {
+    ($level:ident, target: $target:expr, $($arg:tt)+) => {
+        match format_args!($($arg)+) {
+            args => {
+                log::$level!(target: $target, "{}", args);
+                #[cfg(feature = "defmt")]
+                defmt::$level!("[{=str}] {=str}", $target, alloc::fmt::format(args).as_str());
+            }
+        }
+    };
+    ($level:ident, $($arg:tt)+) => {
+        fp_log!($level, target: module_path!(), $($arg)+)
+    };
+}
+
+macro_rules! fp_info {
+    ($($arg:tt)+) => { fp_log!(info, $($arg)+) };
+}
+
+macro_rules! fp_warn {
+    ($($arg:tt)+) => { fp_log!(warn, $($arg)+) };
+}
+
+macro_rules! fp_error {
+    ($($arg:tt)+) => { fp_log!(error, $($arg)+) };
+}
@@ -0,0 +1,133 @@
+#![forbid(unsafe_code)]
+
+// Miroir hôte des templates (option, voir `SensorCtx::enable_mirror`) : copie en RAM
+// du contenu de chaque template stocké, pour une sauvegarde sans aller-retour SPI et
+// pour réécrire un capteur remplacé sans faire ré-enrôler les utilisateurs.
+//
+// Comme `EnrolledCache`, il est tenu à jour par les opérations de ce module
+// (enrôlement, import, suppression, effacement). Une écriture dont l'issue est
+// incertaine le rend incomplet : la prochaine sauvegarde relit alors le capteur et le
+// reconstruit. Il n'est jamais vidé par `deinit` ni par un changement de capteur :
+// c'est justement la copie qui permet de reprovisionner le nouveau.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use anyhow::Result;
+
+use super::sensor::{not_initialized, SensorCtx};
+
+// Contenu des templates par ID ; `complete` à faux s'il a pu diverger du capteur
+pub(super) struct MirrorState {
+    templates: BTreeMap<u16, Vec<u8>>,
+    complete: bool,
+}
+
+/// `None` = miroir désactivé (défaut) : aucune copie, aucun export supplémentaire.
+#[derive(Default)]
+pub(super) struct TemplateMirror(Option<MirrorState>);
+
+impl TemplateMirror {
+    pub(super) const fn new() -> Self {
+        Self(None)
+    }
+
+    pub(super) fn is_enabled(&self) -> bool {
+        self.0.is_some()
+    }
+
+    pub(super) fn store(&mut self, id: u16, data: &[u8]) {
+        if let Some(state) = self.0.as_mut() {
+            state.templates.insert(id, data.to_vec());
+        }
+    }
+
+    pub(super) fn remove(&mut self, id: u16) {
+        if let Some(state) = self.0.as_mut() {
+            state.templates.remove(&id);
+        }
+    }
+
+    pub(super) fn clear(&mut self) {
+        if let Some(state) = self.0.as_mut() {
+            state.templates.clear();
+            state.complete = true;
+        }
+    }
+
+    pub(super) fn invalidate(&mut self) {
+        if let Some(state) = self.0.as_mut() {
+            state.complete = false;
+        }
+    }
+
+    // Templates du miroir, s'il est activé et complet
+    pub(super) fn templates(&self) -> Option<&BTreeMap<u16, Vec<u8>>> {
+        self.0
+            .as_ref()
+            .filter(|state| state.complete)
+            .map(|state| &state.templates)
+    }
+
+    pub(super) fn get(&self, id: u16) -> Option<&[u8]> {
+        self.templates()?.get(&id).map(Vec::as_slice)
+    }
+}
+
+impl SensorCtx {
+    /// Active (ou désactive et libère) le miroir hôte des templates.
+    ///
+    /// Activé, il copie tout de suite chaque template du capteur, puis chaque template
+    /// écrit ensuite (enrôlement, import) ; `export_all` et `export_template` sont alors
+    /// servis depuis la RAM, sans SPI. Sur un capteur remplacé, `import_all(&export_all()?)`
+    /// réécrit les templates sans ré-enrôlement.
+    ///
+    /// Coût : la taille du template exporté (jusqu'à `MAX_TEMPLATE_LEN` = 3 Ko) plus
+    /// quelques dizaines d'octets de `BTreeMap`, par template ; une centaine de
+    /// templates peut donc dépasser 300 Ko. Les templates sont en clair en RAM.
+    pub fn enable_mirror(&mut self, enabled: bool) -> Result<()> {
+        self.check_unlocked("enable_mirror")?;
+        if !enabled {
+            self.mirror = TemplateMirror::new();
+            return Ok(());
+        }
+        self.sync_mirror()
+    }
+
+    // Recopie tous les templates du capteur dans un miroir neuf (complet)
+    pub(super) fn sync_mirror(&mut self) -> Result<()> {
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+        let ids = chain.template_ids()?;
+        let mut templates = BTreeMap::new();
+        for &id in &ids {
+            templates.insert(id, chain.template_export(id)?);
+        }
+        self.enrolled.set(&ids);
+        let bytes: usize = templates.values().map(Vec::len).sum();
+        fp_info!(target: self.log_target, "Miroir des templates : {} templates, {bytes} octets", templates.len());
+        self.mirror = TemplateMirror(Some(MirrorState {
+            templates,
+            complete: true,
+        }));
+        Ok(())
+    }
+
+    // Recopie le template `id` du capteur dans le miroir après une écriture ; sur échec
+    // de lecture, le miroir est marqué incomplet
+    pub(super) fn mirror_fetch(&mut self, id: u16) {
+        if !self.mirror.is_enabled() {
+            return;
+        }
+        let exported = self
+            .chain
+            .as_mut()
+            .ok_or_else(not_initialized)
+            .and_then(|chain| chain.template_export(id));
+        match exported {
+            Ok(data) => self.mirror.store(id, &data),
+            Err(e) => {
+                fp_warn!(target: self.log_target, "Template {id}: copie vers le miroir impossible: {e}");
+                self.mirror.invalidate();
+            }
+        }
+    }
+}
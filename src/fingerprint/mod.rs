@@ -0,0 +1,109 @@
+// Module fingerprint : cœur `SensorCtx` (core + alloc uniquement, verrou fourni
+// par l'appelant via `SensorLock`) et, sous la feature `std`, un singleton global
+// avec l'API libre historique (init, enroll_user, check_once, ...).
+//
+// Backend : `bep` (BM-Lite réel via FFI) ou, sous la feature `sim`, un capteur
+// simulé en mémoire avec la même API, pour faire tourner l'application sur l'hôte.
+
+#[cfg(all(not(feature = "sim"), not(target_os = "espidf")))]
+compile_error!("hors ESP-IDF, seul le backend simulé est disponible : activer la feature `sim`");
+
+// En premier : macros fp_* utilisées par tous les modules
+#[macro_use]
+mod logging;
+
+#[cfg(not(feature = "sim"))]
+mod bep;
+#[cfg(feature = "sim")]
+#[path = "sim.rs"]
+mod bep;
+#[cfg(feature = "std")]
+mod async_wait;
+mod backup;
+mod bench;
+mod cache;
+#[cfg(feature = "cabi")]
+mod cabi;
+mod cancel;
+mod clock;
+#[cfg(feature = "std")]
+mod continuous;
+#[cfg(feature = "crypto")]
+mod crypto;
+mod diagnostics;
+#[cfg(feature = "enroll")]
+mod enroll;
+mod error;
+#[cfg(not(feature = "sim"))]
+mod ffi;
+#[cfg(feature = "std")]
+mod global;
+mod groups;
+mod health;
+mod integrity;
+mod labels;
+#[cfg(not(feature = "sim"))]
+mod link;
+mod lock;
+mod mirror;
+#[cfg(feature = "nvs")]
+mod nvs;
+mod pinned;
+mod pins;
+mod power;
+mod probe;
+mod profile;
+mod quality;
+mod recovery;
+mod security;
+mod sensor;
+#[cfg(feature = "crypto")]
+mod signed;
+mod stats;
+mod table;
+
+pub use bench::{BenchEntry, BenchReport, BENCH_TIMEOUT_MS};
+pub use cancel::CancelToken;
+#[cfg(feature = "std")]
+pub use clock::MockClock;
+pub use clock::{Clock, SystemClock};
+pub use diagnostics::{ControlLineState, DiagnosticsReport, DIAGNOSTICS_PROBE_ITERATIONS};
+#[cfg(feature = "enroll")]
+pub use enroll::{EnrollPreparation, EnrollReport, ExistingPolicy, DEFAULT_MAX_BAD_SAMPLES};
+pub use error::{FingerprintError, RecoveryAction};
+pub use health::{HealthEstimate, HealthStatus};
+pub use integrity::{validate_template_bytes, TemplateMeta};
+pub use labels::MAX_LABEL_LEN;
+#[cfg(feature = "nvs")]
+pub use nvs::NVS_FLUSH_INTERVAL_MS;
+pub use pinned::RemovalReport;
+pub use pins::{BitOrder, DriveStrength, LineConfig, PinConfig, Pull, SpiHost, SpiMode};
+pub use probe::LinkQuality;
+pub use profile::{Profile, FAST_ACCESS_BUDGET_MS, HIGH_SECURITY_MAX_BAD_SAMPLES};
+pub use quality::RepeatabilityScore;
+pub use recovery::DEFAULT_AUTO_RECOVERY_THRESHOLD;
+pub use security::{LockoutPolicy, SecurityProfile, SecurityState};
+#[cfg(feature = "enroll")]
+pub use sensor::CalibrationEvent;
+pub use sensor::{
+    Capabilities, ConnectStatus, CoverageCallback, HcpArgView, IdentifyPolicy, ImageGeometry, InitReport, MatchCallback, MatchGeometry,
+    NoMatchReason, OpTiming, SensorCtx, SensorLock, SensorSnapshot, DEFAULT_LOG_TARGET, DEFAULT_TEMPLATE_CAPACITY,
+    HARDWARE_ID_LEN, MAX_DETECT_SENSITIVITY, MAX_IDENTIFY_TIMEOUT_MS, MAX_TEMPLATE_LEN, MIN_DETECT_SENSITIVITY,
+};
+#[cfg(feature = "crypto")]
+pub use signed::{SignedMatch, SIGNED_MATCH_CONTEXT};
+pub use stats::TemplateStats;
+pub use table::MAX_TRACKED_TEMPLATES;
+
+#[cfg(feature = "sim")]
+pub use bep::{
+    sim_clear_templates, sim_fail_next, sim_seed_template, sim_set_finger, sim_wedge_enroll, SimFault, SimFinger,
+};
+#[cfg(all(feature = "sim", feature = "nvs"))]
+pub use bep::{sim_nvs_blob, sim_set_nvs_blob};
+#[cfg(feature = "std")]
+pub use async_wait::await_finger_then_identify;
+#[cfg(feature = "std")]
+pub use continuous::{is_irq_masked, mask_irq, start_continuous, stop_continuous, unmask_irq};
+#[cfg(feature = "std")]
+pub use global::*;
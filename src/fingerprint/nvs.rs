@@ -0,0 +1,312 @@
+#![forbid(unsafe_code)]
+
+// Persistance des métadonnées hôte en NVS (feature `nvs`) : labels, épinglage,
+// qualités, CRC de référence, groupes, statistiques d'usage et identifiant du
+// capteur (pour détecter un échange entre deux boots).
+//
+// Pour ménager la flash sur une porte très passante, rien n'est écrit à chaque
+// modification : les métadonnées restent en RAM et sont écrites en un seul blob au
+// plus toutes les `NVS_FLUSH_INTERVAL_MS` (à la fin d'un appel du singleton global),
+// à la mise en veille, au `deinit` et sur `flush_nvs`. Un blob identique au dernier
+// écrit n'est pas réécrit. Une coupure perd au plus les modifications de
+// l'intervalle en cours : appeler `flush_nvs` après une opération d'administration.
+//
+// Le blob est relu au premier `init` et remplace les métadonnées déjà en RAM. Un blob
+// écrit avec un `MAX_TRACKED_TEMPLATES` plus grand est relu jusqu'à la capacité, le
+// reste ignoré avec un avertissement.
+// Format (entiers little-endian, comptes en u32) :
+//   en-tête : "FPMD" | version u8 | présence u8 | unique id (12 octets si présent)
+//   labels  : n | (id u16 | longueur u32 | UTF-8)*
+//   épinglés: n | id u16*
+//   qualités: n | (id u16 | qualité u16)*
+//   CRC     : n | (id u16 | crc u32)*
+//   groupes : n | (groupe u16 | m u32 | id u16 * m)*
+//   stats   : n | (id u16 | enrolled_at u64 | last_matched_at u64 | match_count u32)*
+//             (date 0 = inconnue)
+//   fin     : CRC32 (u32) de tout ce qui précède
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use anyhow::{anyhow, Result};
+
+use super::bep::{self, MetaStore, UNIQUE_ID_LEN};
+use super::error::FingerprintError;
+use super::labels::Label;
+use super::sensor::SensorCtx;
+use super::stats::TemplateStats;
+use super::table::{IdTable, MAX_TRACKED_TEMPLATES};
+
+/// Intervalle minimal entre deux écritures périodiques des métadonnées.
+pub const NVS_FLUSH_INTERVAL_MS: u64 = 60_000;
+
+const MAGIC: &[u8; 4] = b"FPMD";
+const VERSION: u8 = 1;
+const CRC_LEN: usize = 4;
+
+impl SensorCtx {
+    /// Partition NVS à utiliser, quand l'application a déjà pris la partition par
+    /// défaut (Wi-Fi, ...) : lui en passer un clone avant `init`. Sans elle, le
+    /// module prend lui-même la partition par défaut.
+    #[cfg(not(feature = "sim"))]
+    pub fn set_nvs_partition(&mut self, partition: bep::NvsPartition) {
+        self.nvs_partition = Some(partition);
+    }
+
+    /// Écrit tout de suite les métadonnées si elles ont changé depuis la dernière
+    /// écriture ; renvoie `true` si la NVS a été écrite. Erreur si le stockage n'a pas
+    /// pu être ouvert au premier `init` (ou si `init` n'a pas encore eu lieu).
+    pub fn flush_nvs(&mut self) -> Result<bool> {
+        self.nvs_flushed_ms = self.clock.now_ms();
+        let blob = self.encode_metadata();
+        let crc = body_crc(&blob);
+        if self.nvs_crc == Some(crc) {
+            return Ok(false);
+        }
+        let store = self
+            .nvs
+            .as_mut()
+            .ok_or_else(|| anyhow!("NVS metadata store not open"))?;
+        store.save(&blob)?;
+        self.nvs_crc = Some(crc);
+        fp_info!(target: self.log_target, "Métadonnées écrites en NVS ({} octets)", blob.len());
+        Ok(true)
+    }
+
+    // Écriture périodique, à la fin des appels du singleton global
+    pub(super) fn flush_nvs_if_due(&mut self) {
+        if self.nvs.is_some()
+            && self.clock.now_ms().saturating_sub(self.nvs_flushed_ms) >= NVS_FLUSH_INTERVAL_MS
+        {
+            self.flush_nvs_logged();
+        }
+    }
+
+    // Veille, deinit : une erreur NVS ne doit pas empêcher l'opération
+    pub(super) fn flush_nvs_logged(&mut self) {
+        if self.nvs.is_none() {
+            return;
+        }
+        if let Err(e) = self.flush_nvs() {
+            fp_warn!(target: self.log_target, "Écriture NVS des métadonnées impossible: {e}");
+        }
+    }
+
+    // Premier `init` : ouvre le stockage et restaure les métadonnées. En cas d'échec,
+    // le module fonctionne sans persistance (nouvel essai au prochain `init`).
+    pub(super) fn open_nvs(&mut self) {
+        if self.nvs.is_some() {
+            return;
+        }
+        let res = MetaStore::open(self.nvs_partition.clone()).and_then(|mut store| {
+            let blob = store.load()?;
+            Ok((store, blob))
+        });
+        let (store, blob) = match res {
+            Ok(opened) => opened,
+            Err(e) => {
+                fp_warn!(target: self.log_target, "NVS indisponible, métadonnées non persistées: {e}");
+                return;
+            }
+        };
+        self.nvs = Some(store);
+        self.nvs_flushed_ms = self.clock.now_ms();
+
+        let Some(blob) = blob else {
+            return;
+        };
+        match self.decode_metadata(&blob) {
+            Ok(()) => {
+                self.nvs_crc = Some(body_crc(&blob));
+                fp_info!(target: self.log_target, "Métadonnées restaurées depuis la NVS");
+            }
+            // Blob illisible : remplacé à la prochaine écriture
+            Err(e) => fp_warn!(target: self.log_target, "Métadonnées NVS ignorées: {e}"),
+        }
+    }
+
+    fn encode_metadata(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        match &self.unique_id {
+            Some(id) => {
+                out.push(1);
+                out.extend_from_slice(id);
+            }
+            None => out.push(0),
+        }
+
+        put_u32(&mut out, self.labels.len() as u32);
+        for (id, label) in self.labels.iter() {
+            put_u16(&mut out, id);
+            put_u32(&mut out, label.as_str().len() as u32);
+            out.extend_from_slice(label.as_str().as_bytes());
+        }
+        put_u32(&mut out, self.pinned.len() as u32);
+        for (id, ()) in self.pinned.iter() {
+            put_u16(&mut out, id);
+        }
+        put_u32(&mut out, self.qualities.len() as u32);
+        for (id, quality) in self.qualities.iter() {
+            put_u16(&mut out, id);
+            put_u16(&mut out, quality);
+        }
+        put_u32(&mut out, self.checksums.len() as u32);
+        for (id, crc) in self.checksums.iter() {
+            put_u16(&mut out, id);
+            put_u32(&mut out, crc);
+        }
+        put_u32(&mut out, self.groups.len() as u32);
+        for (&group_id, members) in &self.groups {
+            put_u16(&mut out, group_id);
+            put_u32(&mut out, members.len() as u32);
+            for &id in members {
+                put_u16(&mut out, id);
+            }
+        }
+        put_u32(&mut out, self.stats.len() as u32);
+        for (id, stats) in self.stats.iter() {
+            put_u16(&mut out, id);
+            put_u64(&mut out, stats.enrolled_at.unwrap_or(0));
+            put_u64(&mut out, stats.last_matched_at.unwrap_or(0));
+            put_u32(&mut out, stats.match_count);
+        }
+
+        let crc = bep::crc32(&out);
+        put_u32(&mut out, crc);
+        out
+    }
+
+    // Tout ou rien : les métadonnées en RAM ne sont remplacées que si le blob est valide
+    fn decode_metadata(&mut self, blob: &[u8]) -> Result<()> {
+        if blob.len() < MAGIC.len() + 2 + CRC_LEN {
+            return Err(FingerprintError::Integrity.into());
+        }
+        let (body, crc) = blob.split_at(blob.len() - CRC_LEN);
+        if bep::crc32(body).to_le_bytes() != crc {
+            return Err(FingerprintError::Integrity.into());
+        }
+        let mut r = Reader { data: body };
+        if r.take(MAGIC.len())? != MAGIC {
+            return Err(FingerprintError::Integrity.into());
+        }
+        let version = r.u8()?;
+        if version != VERSION {
+            return Err(anyhow!("unsupported NVS metadata version {version}"));
+        }
+        let unique_id = match r.u8()? {
+            0 => None,
+            _ => Some(r.array::<UNIQUE_ID_LEN>()?),
+        };
+
+        // Entrées au-delà de `MAX_TRACKED_TEMPLATES` (blob d'un build plus large)
+        let mut dropped = 0u32;
+        let mut labels = IdTable::new();
+        for _ in 0..r.u32()? {
+            let id = r.u16()?;
+            let len = r.u32()?;
+            let label = core::str::from_utf8(r.take(len as usize)?)
+                .map_err(|_| FingerprintError::Integrity)?;
+            dropped += u32::from(!labels.insert(id, Label::new(label)));
+        }
+        let mut pinned = IdTable::new();
+        for _ in 0..r.u32()? {
+            dropped += u32::from(!pinned.insert(r.u16()?, ()));
+        }
+        let mut qualities = IdTable::new();
+        for _ in 0..r.u32()? {
+            dropped += u32::from(!qualities.insert(r.u16()?, r.u16()?));
+        }
+        let mut checksums = IdTable::new();
+        for _ in 0..r.u32()? {
+            dropped += u32::from(!checksums.insert(r.u16()?, r.u32()?));
+        }
+        let mut groups = BTreeMap::new();
+        for _ in 0..r.u32()? {
+            let group_id = r.u16()?;
+            let members = (0..r.u32()?).map(|_| r.u16()).collect::<Result<Vec<_>>>()?;
+            groups.insert(group_id, members);
+        }
+        let mut stats = IdTable::new();
+        for _ in 0..r.u32()? {
+            let id = r.u16()?;
+            let entry = TemplateStats {
+                enrolled_at: Some(r.u64()?).filter(|&t| t != 0),
+                last_matched_at: Some(r.u64()?).filter(|&t| t != 0),
+                match_count: r.u32()?,
+            };
+            dropped += u32::from(!stats.insert(id, entry));
+        }
+        if !r.data.is_empty() {
+            return Err(FingerprintError::Integrity.into());
+        }
+        if dropped > 0 {
+            fp_warn!(target: self.log_target, "Métadonnées NVS : {dropped} entrées au-delà de {MAX_TRACKED_TEMPLATES} IDs ignorées");
+        }
+
+        // Un capteur vu avant le reboot : `check_identity` saura s'il a été remplacé
+        if self.unique_id.is_none() {
+            self.unique_id = unique_id;
+        }
+        self.labels = labels;
+        self.pinned = pinned;
+        self.qualities = qualities;
+        self.checksums = checksums;
+        self.groups = groups;
+        self.stats = stats;
+        Ok(())
+    }
+}
+
+// CRC du contenu, hors CRC final (celui du blob complet serait constant)
+fn body_crc(blob: &[u8]) -> u32 {
+    bep::crc32(&blob[..blob.len().saturating_sub(CRC_LEN)])
+}
+
+fn put_u16(out: &mut Vec<u8>, v: u16) {
+    out.extend_from_slice(&v.to_le_bytes());
+}
+
+fn put_u32(out: &mut Vec<u8>, v: u32) {
+    out.extend_from_slice(&v.to_le_bytes());
+}
+
+fn put_u64(out: &mut Vec<u8>, v: u64) {
+    out.extend_from_slice(&v.to_le_bytes());
+}
+
+// Lecture séquentielle du blob ; toute lecture hors limites est une erreur d'intégrité
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.data.len() < len {
+            return Err(FingerprintError::Integrity.into());
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into()?)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        self.array().map(u16::from_le_bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        self.array().map(u64::from_le_bytes)
+    }
+}
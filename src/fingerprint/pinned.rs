@@ -0,0 +1,46 @@
+#![forbid(unsafe_code)]
+
+// Templates protégés (compte de service / admin) : `wipe_templates` et
+// `remove_templates_in_range` les sautent sauf `force`. La liste est tenue côté
+// hôte et suit les labels : persistée par la feature `nvs`, sinon à recharger au boot
+// par l'application (`pinned_templates` / `pin_template`).
+
+use alloc::vec::Vec;
+use anyhow::{anyhow, Result};
+
+use super::sensor::SensorCtx;
+use super::table::MAX_TRACKED_TEMPLATES;
+
+/// Résultat d'une suppression groupée.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RemovalReport {
+    pub removed: u16,
+    /// IDs épinglés présents sur le capteur et conservés
+    pub skipped: Vec<u16>,
+}
+
+impl SensorCtx {
+    /// Protège le template `id` des suppressions groupées. Aucun accès capteur :
+    /// l'ID peut être épinglé avant d'être enrôlé. Échoue si `MAX_TRACKED_TEMPLATES`
+    /// IDs sont déjà épinglés : une protection n'est jamais ignorée en silence.
+    pub fn pin_template(&mut self, id: u16) -> Result<()> {
+        if !self.pinned.insert(id, ()) {
+            return Err(anyhow!(
+                "invalid pin: {MAX_TRACKED_TEMPLATES} templates already pinned"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Retire la protection de `id` (refusé si la configuration est verrouillée).
+    pub fn unpin_template(&mut self, id: u16) -> Result<()> {
+        self.check_unlocked("unpin_template")?;
+        self.pinned.remove(id);
+        Ok(())
+    }
+
+    /// IDs épinglés, triés.
+    pub fn pinned_templates(&self) -> Vec<u16> {
+        self.pinned.iter().map(|(id, ())| id).collect()
+    }
+}
@@ -0,0 +1,203 @@
+#![forbid(unsafe_code)]
+
+// Câblage BM-Lite <-> ESP32 : presets par carte + personnalisation champ par champ.
+
+use anyhow::{anyhow, Result};
+
+/// Contrôleur SPI utilisé pour le BM-Lite (SPI1 est réservé à la flash).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpiHost {
+    Spi2,
+    Spi3,
+}
+
+/// Mode SPI (CPOL, CPHA) : `Mode0` = horloge au repos basse, échantillonnage sur
+/// le front montant. La valeur de l'enum est le numéro de mode ESP-IDF.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpiMode {
+    Mode0 = 0,
+    Mode1 = 1,
+    Mode2 = 2,
+    Mode3 = 3,
+}
+
+/// Ordre des bits sur MOSI / MISO (les deux lignes suivent le même ordre).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitOrder {
+    MsbFirst,
+    LsbFirst,
+}
+
+/// Résistance de tirage interne d'une ligne de contrôle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pull {
+    Floating,
+    Up,
+    Down,
+}
+
+/// Courant de sortie d'un GPIO (GPIO_DRIVE_CAP_0 à 3 : ~5, 10, 20, 40 mA).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriveStrength {
+    Weakest,
+    Weak,
+    Medium,
+    Strongest,
+}
+
+/// Configuration électrique d'une ligne de contrôle (RST, IRQ), appliquée par
+/// `platform_init` avant le premier reset du BM-Lite.
+///
+/// Défaut (`LineConfig::DEFAULT`) : sans pull, drive moyen, c.-à-d. le comportement
+/// historique (défauts ESP-IDF). Le drive n'a d'effet que sur une sortie (RST).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineConfig {
+    pub pull: Pull,
+    pub drive: DriveStrength,
+}
+
+impl Default for LineConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl LineConfig {
+    pub const DEFAULT: Self = Self {
+        pull: Pull::Floating,
+        drive: DriveStrength::Medium,
+    };
+
+    pub const fn with_pull(mut self, pull: Pull) -> Self {
+        self.pull = pull;
+        self
+    }
+
+    pub const fn with_drive(mut self, drive: DriveStrength) -> Self {
+        self.drive = drive;
+        self
+    }
+}
+
+/// Numéros de GPIO du BM-Lite. Partir d'un preset et ajuster avec les `with_*` :
+///
+///     let pins = PinConfig::esp32s3_devkit().with_irq(4).with_rst(5);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PinConfig {
+    pub spi_host: SpiHost,
+    pub cs: i32,
+    pub miso: i32,
+    pub mosi: i32,
+    pub clk: i32,
+    pub rst: i32,
+    pub irq: i32,
+    pub rst_line: LineConfig,
+    pub irq_line: LineConfig,
+    pub spi_mode: SpiMode,
+    pub bit_order: BitOrder,
+}
+
+impl Default for PinConfig {
+    fn default() -> Self {
+        Self::esp32s3_devkit()
+    }
+}
+
+impl PinConfig {
+    /// ESP32-S3 DevKit (câblage d'origine du projet, utilisé par `init`).
+    pub const fn esp32s3_devkit() -> Self {
+        Self {
+            spi_host: SpiHost::Spi2,
+            cs: 45,
+            miso: 37,
+            mosi: 35,
+            clk: 36,
+            rst: 48,
+            irq: 16,
+            rst_line: LineConfig::DEFAULT,
+            irq_line: LineConfig::DEFAULT,
+            spi_mode: SpiMode::Mode0,
+            bit_order: BitOrder::MsbFirst,
+        }
+    }
+
+    pub const fn with_spi_host(mut self, host: SpiHost) -> Self {
+        self.spi_host = host;
+        self
+    }
+
+    pub const fn with_cs(mut self, gpio: i32) -> Self {
+        self.cs = gpio;
+        self
+    }
+
+    pub const fn with_miso(mut self, gpio: i32) -> Self {
+        self.miso = gpio;
+        self
+    }
+
+    pub const fn with_mosi(mut self, gpio: i32) -> Self {
+        self.mosi = gpio;
+        self
+    }
+
+    pub const fn with_clk(mut self, gpio: i32) -> Self {
+        self.clk = gpio;
+        self
+    }
+
+    pub const fn with_rst(mut self, gpio: i32) -> Self {
+        self.rst = gpio;
+        self
+    }
+
+    pub const fn with_irq(mut self, gpio: i32) -> Self {
+        self.irq = gpio;
+        self
+    }
+
+    /// Pull / drive de RST, par ex. un pull-up si la ligne flotte pendant le boot.
+    pub const fn with_rst_line(mut self, line: LineConfig) -> Self {
+        self.rst_line = line;
+        self
+    }
+
+    pub const fn with_irq_line(mut self, line: LineConfig) -> Self {
+        self.irq_line = line;
+        self
+    }
+
+    /// Mode SPI appliqué par `platform_init` (défaut `Mode0`, celui du BM-Lite).
+    /// À changer seulement si un analyseur logique montre un décalage d'échantillonnage.
+    pub const fn with_spi_mode(mut self, mode: SpiMode) -> Self {
+        self.spi_mode = mode;
+        self
+    }
+
+    /// Ordre des bits (défaut `MsbFirst`).
+    pub const fn with_bit_order(mut self, order: BitOrder) -> Self {
+        self.bit_order = order;
+        self
+    }
+
+    /// Vérifie que chaque signal a son propre GPIO, dans la plage de l'ESP32-S3.
+    pub fn validate(&self) -> Result<()> {
+        let pins = [
+            ("cs", self.cs),
+            ("miso", self.miso),
+            ("mosi", self.mosi),
+            ("clk", self.clk),
+            ("rst", self.rst),
+            ("irq", self.irq),
+        ];
+        for (i, &(name, gpio)) in pins.iter().enumerate() {
+            if !(0..=48).contains(&gpio) {
+                return Err(anyhow!("invalid GPIO {gpio} for {name}"));
+            }
+            if let Some(&(other, _)) = pins[..i].iter().find(|&&(_, g)| g == gpio) {
+                return Err(anyhow!("GPIO {gpio} assigned to both {other} and {name}"));
+            }
+        }
+        Ok(())
+    }
+}
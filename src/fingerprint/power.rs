@@ -0,0 +1,74 @@
+#![forbid(unsafe_code)]
+
+// Mise en veille entre deux usages (produits sur batterie) : plus léger que
+// `deinit` + `init`, le bus SPI, les GPIO et les buffers HCP restent alloués.
+//
+// Le protocole HCP du BM-Lite n'a pas de commande de veille : le capteur est tenu en
+// reset (RST bas), son état de plus faible consommation. Ses données (templates,
+// calibration) sont en flash et survivent. Avec la feature `nvs`, `suspend` écrit
+// les métadonnées en attente avant de couper le capteur : un deep sleep ESP (RAM
+// perdue) ne les emporte pas. Sans elle, l'application les sauvegarde elle-même.
+
+use anyhow::Result;
+
+use super::bep;
+use super::sensor::{not_initialized, SensorCtx};
+
+impl SensorCtx {
+    /// Met le capteur en veille (tenu en reset) jusqu'à `resume`. Sans effet s'il
+    /// l'est déjà.
+    ///
+    /// En veille, les opérations capteur renvoient `FingerprintError::NotInitialized`
+    /// (au lieu d'expirer), `init` reprend automatiquement et `deinit` libère tout.
+    pub fn suspend(&mut self) -> Result<()> {
+        if self.parked.is_some() {
+            return Ok(());
+        }
+        if self.chain.is_none() {
+            return Err(not_initialized());
+        }
+        #[cfg(feature = "nvs")]
+        self.flush_nvs_logged();
+        let chain = self.chain.take().ok_or_else(not_initialized)?;
+        let board = self.board.as_mut().ok_or_else(not_initialized)?;
+        bep::hold_reset(board, true);
+        self.parked = Some(chain);
+        fp_info!(target: self.log_target, "BM-Lite: en veille");
+        Ok(())
+    }
+
+    /// Sort de veille : fin du reset, resynchronisation HCP puis vérification de
+    /// l'identifiant (un capteur remplacé pendant la veille invalide les caches).
+    /// Sans effet si le capteur n'est pas en veille.
+    pub fn resume(&mut self) -> Result<()> {
+        let Some(chain) = self.parked.take() else {
+            return if self.chain.is_some() {
+                Ok(())
+            } else {
+                Err(not_initialized())
+            };
+        };
+        self.chain = Some(chain);
+        let board = self.board.as_mut().ok_or_else(not_initialized)?;
+        bep::hw_reset(board);
+        if let Some(chain) = self.chain.as_mut() {
+            chain.reset_link();
+        }
+        let status = self.check_identity()?;
+        fp_info!(target: self.log_target, "BM-Lite: sortie de veille ({status:?})");
+        Ok(())
+    }
+
+    // `init` sur un capteur en veille : simple reprise
+    pub(super) fn resume_if_suspended(&mut self) -> Result<()> {
+        if self.parked.is_some() {
+            self.resume()?;
+        }
+        Ok(())
+    }
+
+    /// Capteur en veille (`suspend`).
+    pub fn is_suspended(&self) -> bool {
+        self.parked.is_some()
+    }
+}
@@ -0,0 +1,89 @@
+#![forbid(unsafe_code)]
+
+// Mesure de la fiabilité du lien SPI à la mise au point : `probe_link` enchaîne des
+// échanges HCP courts à l'horloge configurée et compte ceux qui échouent.
+//
+// Le BM-Lite n'a pas de commande d'écho : le paquet de test est une lecture de la
+// version firmware (CMD_INFO), sans effet sur le capteur. HCP ne protège une trame
+// que par son CRC32 et la couche lien ne retransmet pas : on mesure donc un taux de
+// trames perdues ou corrompues, pas un taux d'erreur par octet, et une trame en
+// échec est un échange perdu (pas de compteur de retransmissions). Un taux non nul
+// invite à baisser l'horloge SPI ou à revoir le câblage.
+
+use anyhow::{anyhow, Result};
+
+use super::error::{FingerprintError, BEP_RESULT_IO_ERROR};
+use super::sensor::{not_initialized, SensorCtx};
+
+/// Bilan de `probe_link`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LinkQuality {
+    pub iterations: u32,
+    /// Trames corrompues ou incomplètes (CRC, ACK, contention)
+    pub io_errors: u32,
+    /// Réponses non reçues à temps
+    pub timeouts: u32,
+    /// Échanges en échec pour mille
+    pub error_rate_permille: u32,
+    /// Durée moyenne d'un échange réussi, en µs
+    pub mean_latency_us: u64,
+}
+
+impl SensorCtx {
+    /// Envoie `iterations` paquets de test et relève les échecs de transmission.
+    /// Une erreur autre qu'une trame perdue (capteur non initialisé, ...) interrompt
+    /// la mesure.
+    pub fn probe_link(&mut self, iterations: u32) -> Result<LinkQuality> {
+        if iterations == 0 {
+            return Err(anyhow!("invalid probe iterations: 0"));
+        }
+        if self.chain.is_none() {
+            return Err(not_initialized());
+        }
+
+        let mut quality = LinkQuality {
+            iterations,
+            ..LinkQuality::default()
+        };
+        let mut total_us = 0;
+        // Pas de récupération automatique pendant la mesure : elle la fausserait
+        let auto_recovery = core::mem::replace(&mut self.auto_recovery_threshold, 0);
+        for _ in 0..iterations {
+            let res = self.timed(|ctx| {
+                ctx.chain
+                    .as_mut()
+                    .ok_or_else(not_initialized)?
+                    .firmware_version()
+            });
+            match res {
+                Ok(_) => total_us += self.last_timing.map_or(0, |t| t.total_us),
+                Err(e) => match FingerprintError::of(&e) {
+                    Some(FingerprintError::Bep { code, .. }) if code == BEP_RESULT_IO_ERROR => {
+                        quality.io_errors += 1
+                    }
+                    Some(FingerprintError::BusContention(_)) => quality.io_errors += 1,
+                    Some(FingerprintError::Timeout(_)) => quality.timeouts += 1,
+                    _ => {
+                        self.auto_recovery_threshold = auto_recovery;
+                        return Err(e);
+                    }
+                },
+            }
+        }
+        self.auto_recovery_threshold = auto_recovery;
+
+        let failed = quality.io_errors + quality.timeouts;
+        quality.error_rate_permille = (u64::from(failed) * 1000 / u64::from(iterations)) as u32;
+        quality.mean_latency_us = total_us
+            .checked_div(u64::from(iterations - failed))
+            .unwrap_or(0);
+        fp_info!(
+            target: self.log_target,
+            "Lien SPI : {failed}/{iterations} échanges en échec ({} ‰), {} µs en moyenne",
+            quality.error_rate_permille,
+            quality.mean_latency_us
+        );
+        Ok(quality)
+    }
+}
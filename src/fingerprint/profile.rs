@@ -0,0 +1,58 @@
+#![forbid(unsafe_code)]
+
+// Profils d'installation : un point de fonctionnement cohérent en un appel, sans
+// passer en revue chaque réglage.
+//
+// Seuls les réglages tenus côté hôte sont appliqués. Le BM-Lite n'expose ni nombre
+// d'échantillons d'enrôlement, ni couverture minimale, ni niveau de sécurité, ni
+// détection du vivant (aucun argument HCP) : ceux-là restent ceux du firmware, quel
+// que soit le profil, et `apply_profile` n'échoue pas pour autant.
+
+use anyhow::Result;
+
+use super::sensor::{IdentifyPolicy, SensorCtx};
+
+/// Point de fonctionnement appliqué par `SensorCtx::apply_profile`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    /// Passage fréquent : deux tentatives enchaînées sans pause, bornées à
+    /// `FAST_ACCESS_BUDGET_MS`, et enrôlement tolérant aux mauvais échantillons
+    FastAccess,
+    /// Une seule tentative (chaque tentative est une occasion de fausse acceptation)
+    /// et enrôlement abandonné après `HIGH_SECURITY_MAX_BAD_SAMPLES` mauvais
+    /// échantillons, plutôt que de stocker un template médiocre
+    HighSecurity,
+}
+
+/// Durée maximale d'une identification en `Profile::FastAccess`.
+pub const FAST_ACCESS_BUDGET_MS: u32 = 3_000;
+/// Mauvais échantillons tolérés à l'enrôlement en `Profile::HighSecurity`.
+pub const HIGH_SECURITY_MAX_BAD_SAMPLES: u8 = 3;
+
+impl Profile {
+    fn identify_policy(self) -> IdentifyPolicy {
+        match self {
+            Self::FastAccess => IdentifyPolicy {
+                attempts: 2,
+                backoff_ms: 0,
+                budget_ms: FAST_ACCESS_BUDGET_MS,
+            },
+            Self::HighSecurity => IdentifyPolicy::SINGLE,
+        }
+    }
+}
+
+impl SensorCtx {
+    /// Applique les réglages hôte de `profile` (politique d'identification et, avec la
+    /// feature `enroll`, tolérance de l'enrôlement). Les autres réglages ne sont pas
+    /// touchés ; un réglage ultérieur remplace celui du profil.
+    pub fn apply_profile(&mut self, profile: Profile) -> Result<()> {
+        self.set_identify_policy(profile.identify_policy())?;
+        #[cfg(feature = "enroll")]
+        self.set_enroll_retry(match profile {
+            Profile::FastAccess => super::enroll::DEFAULT_MAX_BAD_SAMPLES,
+            Profile::HighSecurity => HIGH_SECURITY_MAX_BAD_SAMPLES,
+        })?;
+        Ok(())
+    }
+}
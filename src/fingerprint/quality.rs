@@ -0,0 +1,81 @@
+#![forbid(unsafe_code)]
+
+// Qualité d'enrôlement par template, pour repérer les enrôlements faibles et les
+// refaire avant qu'ils ne provoquent des faux rejets.
+//
+// Le BM-Lite ne renvoie aucun score : la qualité est la part d'échantillons acceptés
+// pendant l'enrôlement (`EnrollReport`), de 0 à 100. Conservée avec les labels
+// (feature `nvs`, ou `set_template_quality` au boot). Un template importé (backup, chiffré) n'a pas de qualité.
+//
+// `assess_finger` juge un doigt avant son enrôlement : deux poses, la seconde
+// comparée au template extrait de la première (CMD_MATCH, comme `verify_transient`).
+
+use alloc::vec::Vec;
+use anyhow::Result;
+
+use super::sensor::{not_initialized, SensorCtx};
+
+/// Répétabilité d'un doigt mesurée par `SensorCtx::assess_finger`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RepeatabilityScore {
+    /// La seconde pose matche la première, au seuil de match du firmware
+    pub matched: bool,
+    /// Score de similarité brut du firmware, s'il en rapporte un (jamais le BM-Lite,
+    /// voir `HcpArgView`)
+    pub score: Option<u16>,
+}
+
+impl RepeatabilityScore {
+    /// Vrai si le doigt devrait être reconnu de façon fiable une fois enrôlé.
+    pub fn is_reliable(&self) -> bool {
+        self.matched
+    }
+}
+
+impl SensorCtx {
+    /// Qualité (0 à 100) mémorisée à l'enrôlement du template `id`, `None` si
+    /// inconnue (template importé, enrôlé avant le boot sans restauration, ...).
+    pub fn template_quality(&self, id: u16) -> Option<u16> {
+        self.qualities.get(id)
+    }
+
+    /// Restaure une qualité sauvegardée (valeur bornée à 100). Ignorée si `id` est
+    /// nouveau et que `MAX_TRACKED_TEMPLATES` IDs ont déjà une qualité.
+    pub fn set_template_quality(&mut self, id: u16, quality: u16) {
+        if !self.qualities.insert(id, quality.min(100)) {
+            fp_warn!(target: self.log_target, "Template {id}: qualité ignorée, table pleine");
+        }
+    }
+
+    /// Toutes les qualités connues, triées par ID.
+    pub fn template_qualities(&self) -> Vec<(u16, u16)> {
+        self.qualities.iter().collect()
+    }
+
+    /// Mesure la répétabilité du doigt avant de l'enrôler : une première capture,
+    /// retrait du doigt, une seconde capture comparée à la première. Un doigt qui ne
+    /// se reconnaît pas lui-même (`is_reliable` faux : peau très sèche ou abîmée, pose
+    /// instable) donnera des faux rejets une fois enrôlé ; un assistant d'installation
+    /// peut le refuser ou proposer un autre doigt.
+    ///
+    /// Rien n'est écrit en flash et le verrouillage n'est pas concerné (ce n'est pas un
+    /// accès). `timeout_ms` s'applique à chaque pose et au retrait ; un doigt absent ou
+    /// non retiré donne `FingerprintError::Timeout`. Le BM-Lite ne rend qu'un verdict
+    /// de match : la mesure est binaire, `score` reste `None`.
+    pub fn assess_finger(&mut self, timeout_ms: u32) -> Result<RepeatabilityScore> {
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+        let timeout = timeout_ms.clamp(1, u32::from(u16::MAX)) as u16;
+
+        let probe = chain.probe_export(timeout)?;
+        // Deux poses distinctes : sans retrait, la seconde image serait la même pose
+        chain.wait_finger_not_present(timeout)?;
+        let matched = chain.verify_transient(&probe, timeout)?;
+        let score = RepeatabilityScore {
+            matched,
+            score: chain.read_arg().score,
+        };
+        fp_info!(target: self.log_target, "Répétabilité du doigt : {}", if matched { "fiable" } else { "insuffisante" });
+        Ok(score)
+    }
+}
@@ -0,0 +1,112 @@
+#![forbid(unsafe_code)]
+
+// Récupération automatique du lien après des échecs de communication répétés.
+//
+// Chaque opération passée par `timed` (identification, enrôlement, calibration, ...)
+// met à jour le compteur d'échecs consécutifs : +1 sur une erreur de lien (trame
+// perdue, contention), remis à zéro sur toute réponse du capteur, même un refus. Au
+// seuil, l'échelle de récupération est descendue jusqu'à la première étape qui
+// rétablit le lien :
+//
+//   1) reset HCP : état hôte remis à zéro et réponses en attente vidées (`reset_hcp`)
+//   2) reset logiciel : CMD_RESET, le firmware redémarre
+//   3) reset matériel : broche RST (`reconnect`)
+//   4) réinit complète : `deinit` puis `init` avec le même câblage
+//
+// L'opération en échec n'est pas rejouée : son erreur est renvoyée telle quelle, la
+// suivante trouve le lien rétabli.
+
+use anyhow::Result;
+
+use super::error::is_link_error;
+use super::sensor::{not_initialized, SensorCtx};
+
+/// Échecs de communication consécutifs déclenchant la récupération (défaut).
+pub const DEFAULT_AUTO_RECOVERY_THRESHOLD: u8 = 3;
+
+// Redémarrage du firmware après CMD_RESET (comme après un reset matériel)
+const SOFT_RESET_BOOT_MS: u32 = 100;
+
+// Échelle de récupération, de l'étape la plus légère à la plus lourde
+#[derive(Debug, Clone, Copy)]
+enum RecoveryStep {
+    ResetHcp,
+    SoftReset,
+    HardReset,
+    Reinit,
+}
+
+const LADDER: [RecoveryStep; 4] = [
+    RecoveryStep::ResetHcp,
+    RecoveryStep::SoftReset,
+    RecoveryStep::HardReset,
+    RecoveryStep::Reinit,
+];
+
+impl SensorCtx {
+    /// Échecs de communication consécutifs déclenchant la récupération automatique
+    /// (défaut : `DEFAULT_AUTO_RECOVERY_THRESHOLD`) ; `0` la désactive, le compteur
+    /// reste tenu. La réinit complète, dernière étape, échoue si la configuration est
+    /// verrouillée (`lock_config`).
+    pub fn set_auto_recovery(&mut self, threshold: u8) -> Result<()> {
+        self.check_unlocked("set_auto_recovery")?;
+        self.auto_recovery_threshold = threshold;
+        Ok(())
+    }
+
+    /// Échecs de communication consécutifs depuis la dernière réponse du capteur.
+    pub fn comms_failures(&self) -> u8 {
+        self.comms_failures
+    }
+
+    // Compte l'issue d'une opération et lance la récupération au seuil
+    pub(super) fn track_comms<T>(&mut self, res: &Result<T>) {
+        match res {
+            Err(e) if is_link_error(e) => {
+                self.comms_failures = self.comms_failures.saturating_add(1);
+                let threshold = self.auto_recovery_threshold;
+                if threshold != 0 && self.comms_failures >= threshold && self.is_initialized() {
+                    self.auto_recover();
+                }
+            }
+            _ => self.comms_failures = 0,
+        }
+    }
+
+    fn auto_recover(&mut self) {
+        fp_warn!(target: self.log_target, "BM-Lite: {} échecs de communication consécutifs, récupération...", self.comms_failures);
+        for step in LADDER {
+            match self.recovery_step(step) {
+                Ok(()) => {
+                    fp_info!(target: self.log_target, "BM-Lite: lien rétabli ({step:?})");
+                    self.comms_failures = 0;
+                    return;
+                }
+                Err(e) => {
+                    fp_warn!(target: self.log_target, "BM-Lite: récupération {step:?} en échec: {e}");
+                }
+            }
+        }
+        // Compteur conservé : le prochain échec relance l'échelle
+        fp_error!(target: self.log_target, "BM-Lite: récupération automatique impossible");
+    }
+
+    // Une étape, vérifiée par un aller-retour avec le capteur
+    fn recovery_step(&mut self, step: RecoveryStep) -> Result<()> {
+        match step {
+            RecoveryStep::ResetHcp => self.reset_hcp(),
+            RecoveryStep::SoftReset => {
+                let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+                chain.sw_reset()?;
+                self.clock.sleep_ms(SOFT_RESET_BOOT_MS);
+                self.reset_hcp()
+            }
+            RecoveryStep::HardReset => self.reconnect().map(|_| ()),
+            RecoveryStep::Reinit => {
+                let pins = self.pins.ok_or_else(not_initialized)?;
+                self.deinit()?;
+                self.init_with_pins(&pins).map(|_| ())
+            }
+        }
+    }
+}
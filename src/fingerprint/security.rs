@@ -0,0 +1,204 @@
+#![forbid(unsafe_code)]
+
+// Résumé du niveau de sécurité effectif, pour la documentation d'installation.
+//
+// Le BM-Lite n'expose ni niveau de sécurité ni seuil de match (aucun argument HCP
+// pour les lire ou les régler) et ne rapporte pas de taux d'erreur : les taux
+// nominaux restent `None` tant qu'un firmware ne les fournit pas, plutôt que des
+// valeurs recopiées d'une fiche produit qui ne correspondraient pas forcément à la
+// version installée. Ce qui est connu côté hôte, c'est l'effet de la configuration :
+// chaque tentative et chaque template stocké est une occasion de fausse acceptation.
+//
+// Contre la force brute, le seuil de match ne peut donc pas être relevé : la riposte
+// possible côté hôte est un verrouillage temporaire (`LockoutPolicy`). Après
+// `max_failures` non-matchs consécutifs dans une fenêtre de `window_ms`, toute
+// identification est refusée pendant `lockout_ms` (`FingerprintError::LockedOut`).
+// Un match remet le compteur à zéro ; un non-match hors fenêtre ouvre une nouvelle
+// série. L'état vit en RAM : un reboot lève le verrouillage.
+
+use anyhow::{anyhow, Result};
+
+use super::error::FingerprintError;
+use super::sensor::SensorCtx;
+
+/// Verrouillage après des non-matchs répétés (voir `SensorCtx::set_lockout_policy`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockoutPolicy {
+    /// Non-matchs consécutifs déclenchant le verrouillage, `0` = désactivé
+    pub max_failures: u8,
+    /// Fenêtre dans laquelle les non-matchs sont comptés, depuis le premier de la série
+    pub window_ms: u32,
+    /// Durée du verrouillage
+    pub lockout_ms: u32,
+}
+
+impl LockoutPolicy {
+    /// Pas de verrouillage (défaut)
+    pub const DISABLED: Self = Self {
+        max_failures: 0,
+        window_ms: 0,
+        lockout_ms: 0,
+    };
+}
+
+impl Default for LockoutPolicy {
+    fn default() -> Self {
+        Self::DISABLED
+    }
+}
+
+/// État du verrouillage adaptatif (voir `SensorCtx::security_state`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SecurityState {
+    /// Non-matchs de la série en cours
+    pub consecutive_failures: u8,
+    /// Temps restant avant la levée du verrouillage, `0` si déverrouillé
+    pub lockout_remaining_ms: u32,
+    /// Verrouillages déclenchés depuis le boot (alarme, journal d'accès)
+    pub lockouts: u32,
+}
+
+// Série de non-matchs et verrouillage en cours, tenus par l'identification
+pub(super) struct LockoutTracker {
+    policy: LockoutPolicy,
+    failures: u8,
+    series_start_ms: u64,
+    locked_until_ms: u64,
+    lockouts: u32,
+}
+
+impl LockoutTracker {
+    pub(super) const fn new() -> Self {
+        Self {
+            policy: LockoutPolicy::DISABLED,
+            failures: 0,
+            series_start_ms: 0,
+            locked_until_ms: 0,
+            lockouts: 0,
+        }
+    }
+
+    fn remaining_ms(&self, now: u64) -> u32 {
+        self.locked_until_ms
+            .saturating_sub(now)
+            .min(u64::from(u32::MAX)) as u32
+    }
+
+    // Changement d'horloge (`set_clock`) : le verrouillage en cours garde sa durée
+    // restante, la série de non-matchs repart de zéro
+    pub(super) fn rebase(&mut self, old_now: u64, new_now: u64) {
+        let remaining = self.remaining_ms(old_now);
+        self.locked_until_ms = if remaining > 0 {
+            new_now + u64::from(remaining)
+        } else {
+            0
+        };
+        self.failures = 0;
+        self.series_start_ms = new_now;
+    }
+
+    // Renvoie `true` si ce non-match déclenche le verrouillage
+    fn record_failure(&mut self, now: u64) -> bool {
+        let policy = self.policy;
+        if policy.max_failures == 0 {
+            return false;
+        }
+        if self.failures == 0
+            || now.saturating_sub(self.series_start_ms) > u64::from(policy.window_ms)
+        {
+            self.failures = 0;
+            self.series_start_ms = now;
+        }
+        self.failures += 1;
+        if self.failures < policy.max_failures {
+            return false;
+        }
+        self.failures = 0;
+        self.locked_until_ms = now + u64::from(policy.lockout_ms);
+        self.lockouts = self.lockouts.saturating_add(1);
+        true
+    }
+}
+
+/// Réglages de sécurité en vigueur et leur effet sur le risque de fausse acceptation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SecurityProfile {
+    /// Niveau / seuil de match du firmware (`None` : fixe et non rapporté par le BM-Lite)
+    pub match_threshold: Option<u16>,
+    /// FAR nominal d'une comparaison doigt / template (`None` : non rapporté)
+    pub nominal_far: Option<f32>,
+    /// FRR nominal d'une tentative (`None` : non rapporté)
+    pub nominal_frr: Option<f32>,
+    /// Tentatives par identification (`IdentifyPolicy::attempts`)
+    pub attempts: u8,
+    pub template_count: u16,
+    /// Comparaisons par identification (`attempts * template_count`) : le FAR
+    /// effectif vaut environ `nominal_far * far_multiplier` (FAR faible)
+    pub far_multiplier: u32,
+}
+
+impl SensorCtx {
+    /// Résumé de la configuration de sécurité (voir `SecurityProfile`). Lit le
+    /// nombre de templates depuis le cache (voir `refresh_cache`).
+    pub fn security_profile(&mut self) -> Result<SecurityProfile> {
+        let template_count = self.enrolled_count()?;
+        let attempts = self.identify_policy.attempts.max(1);
+        let far_multiplier = u32::from(attempts) * u32::from(template_count);
+
+        Ok(SecurityProfile {
+            match_threshold: None,
+            nominal_far: None,
+            nominal_frr: None,
+            attempts,
+            template_count,
+            far_multiplier,
+        })
+    }
+
+    /// Verrouillage temporaire après des non-matchs répétés (désactivé par défaut).
+    /// Remet la série en cours à zéro ; un verrouillage en cours est conservé.
+    pub fn set_lockout_policy(&mut self, policy: LockoutPolicy) -> Result<()> {
+        self.check_unlocked("set_lockout_policy")?;
+        if policy.max_failures > 0 && (policy.window_ms == 0 || policy.lockout_ms == 0) {
+            return Err(anyhow!(
+                "invalid lockout policy: zero window or lockout duration"
+            ));
+        }
+        self.lockout.policy = policy;
+        self.lockout.failures = 0;
+        Ok(())
+    }
+
+    /// Série de non-matchs en cours et verrouillage (voir `LockoutPolicy`).
+    pub fn security_state(&self) -> SecurityState {
+        SecurityState {
+            consecutive_failures: self.lockout.failures,
+            lockout_remaining_ms: self.lockout.remaining_ms(self.clock.now_ms()),
+            lockouts: self.lockout.lockouts,
+        }
+    }
+
+    // Avant une identification : refusée pendant un verrouillage
+    pub(super) fn check_lockout(&self) -> Result<()> {
+        match self.lockout.remaining_ms(self.clock.now_ms()) {
+            0 => Ok(()),
+            retry_in_ms => Err(FingerprintError::LockedOut { retry_in_ms }.into()),
+        }
+    }
+
+    // Après une identification aboutie (les erreurs ne comptent pas) : un match remet
+    // la série à zéro, un non-match la prolonge
+    pub(super) fn record_access(&mut self, matched: bool) {
+        if matched {
+            self.lockout.failures = 0;
+        } else if self.lockout.record_failure(self.clock.now_ms()) {
+            fp_warn!(
+                target: self.log_target,
+                "{} non-matchs consécutifs : identification verrouillée {} ms",
+                self.lockout.policy.max_failures,
+                self.lockout.policy.lockout_ms
+            );
+        }
+    }
+}
@@ -0,0 +1,1473 @@
+#![forbid(unsafe_code)]
+
+use alloc::{collections::BTreeMap, string::{String, ToString}, vec::Vec};
+use anyhow::{anyhow, Result};
+
+use core::ffi::c_void;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use super::bep::{self, Board, Chain, UNIQUE_ID_LEN};
+use super::cache::EnrolledCache;
+use super::cancel::CancelToken;
+use super::clock::ClockSource;
+#[cfg(feature = "enroll")]
+use super::enroll::EnrollSettings;
+use super::error::{FingerprintError, BEP_RESULT_IMAGE_CAPTURE_ERROR};
+use super::health::{CalibrationTracker, HealthEstimate};
+use super::integrity::validate_template_bytes;
+use super::labels::Label;
+use super::mirror::TemplateMirror;
+use super::pinned::RemovalReport;
+use super::recovery::DEFAULT_AUTO_RECOVERY_THRESHOLD;
+use super::pins::{PinConfig, SpiHost};
+use super::security::LockoutTracker;
+use super::stats::TemplateStats;
+use super::table::IdTable;
+
+// ======================================================
+// 4) Contexte du capteur
+// ======================================================
+
+/// Target `log` par défaut de tous les messages du module (filtrable côté logger).
+pub const DEFAULT_LOG_TARGET: &str = "fingerprint";
+
+/// Nombre de templates utilisé quand le firmware ne rapporte pas sa capacité
+/// (capacité de stockage annoncée par FPC pour le BM-Lite).
+pub const DEFAULT_TEMPLATE_CAPACITY: u16 = 5;
+
+/// Timeout maximal d'une identification (`check_once` et variantes) : limite 16 bits
+/// de l'attente doigt du firmware. Au-delà, la valeur est ramenée ici (avec un warning).
+pub const MAX_IDENTIFY_TIMEOUT_MS: u32 = u16::MAX as u32;
+
+/// Bornes de `SensorCtx::set_detect_sensitivity`.
+pub const MIN_DETECT_SENSITIVITY: u8 = 1;
+pub const MAX_DETECT_SENSITIVITY: u8 = 10;
+
+/// Largeur en octets de l'identifiant matériel (`bep_unique_id_get`).
+pub const HARDWARE_ID_LEN: usize = UNIQUE_ID_LEN;
+
+/// Taille maximale d'un template exporté : il transite en entier par le buffer de
+/// paquets HCP (voir `SensorCtx::export_template_into`).
+pub const MAX_TEMPLATE_LEN: usize = 3 * 1024;
+
+/// Action exécutée par le module à chaque match, avec l'ID du template
+/// (voir `SensorCtx::on_match`).
+pub type MatchCallback = Box<dyn FnMut(u16) + Send>;
+
+/// Action appelée avec la couverture du capteur par le doigt, en % (voir
+/// `SensorCtx::on_coverage`).
+pub type CoverageCallback = Box<dyn FnMut(u8) + Send>;
+
+/// Résultat de la vérification d'identité du capteur à l'init / reconnexion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectStatus {
+    /// Aucun identifiant mémorisé jusqu'ici (premier init depuis le boot)
+    FirstSeen,
+    /// Même capteur qu'à la connexion précédente
+    SameSensor,
+    /// Le capteur a été remplacé : l'état mis en cache côté hôte doit être resynchronisé
+    SensorChanged,
+    /// Le BM-Lite répond depuis son bootloader (mise à jour firmware en attente) : les
+    /// commandes biométriques échoueront. Le lien est initialisé, `deinit` le libère.
+    InBootloader,
+}
+
+/// Fonctions disponibles sur ce capteur avec ce build.
+///
+/// Le firmware BM-Lite ne décrit pas ses capacités : elles sont déduites de la
+/// version du protocole HCP et des features Cargo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Voir `SensorCtx::max_template_capacity`
+    pub template_capacity: u16,
+    /// Feature `enroll` active (enrôlement, calibration, import de templates)
+    pub enroll: bool,
+    /// Score de match renvoyé par le firmware (jamais sur BM-Lite, voir `HcpArgView`)
+    pub match_score: bool,
+    /// Position du match renvoyée par le firmware (jamais sur BM-Lite, voir
+    /// `SensorCtx::last_match_geometry`)
+    pub match_geometry: bool,
+    /// Couverture du doigt pendant la capture (jamais sur BM-Lite, voir
+    /// `SensorCtx::on_coverage`)
+    pub finger_coverage: bool,
+    /// Enrôlement de plusieurs orientations du doigt (jamais sur BM-Lite, voir
+    /// `SensorCtx::set_enroll_orientations`)
+    pub enroll_orientations: bool,
+    /// Nouveauté de la zone du doigt couverte par chaque échantillon d'enrôlement
+    /// (jamais sur BM-Lite, voir `SensorCtx::set_min_distinct_areas`)
+    pub enroll_area_novelty: bool,
+}
+
+/// Géométrie des images du capteur (voir `SensorCtx::image_geometry`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageGeometry {
+    /// Largeur en pixels
+    pub width: u16,
+    /// Hauteur en pixels
+    pub height: u16,
+    pub dpi: u16,
+}
+
+/// Résumé de l'init (`SensorCtx::init_with_report`), pour éviter les requêtes de suivi.
+///
+/// Les champs lus sur le capteur sont `None` si la lecture a échoué : comme pour
+/// `init`, ces échecs ne font pas échouer l'initialisation (ils sont loggés).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InitReport {
+    pub status: ConnectStatus,
+    pub firmware: Option<String>,
+    pub hardware_id: Option<[u8; HARDWARE_ID_LEN]>,
+    pub template_count: Option<u16>,
+    pub capabilities: Capabilities,
+}
+
+/// Métadonnées en lecture seule, copiées à la fin de chaque opération capteur.
+///
+/// Ce sont des valeurs mises en cache : elles reflètent l'état à la fin de la
+/// dernière opération terminée (pendant une identification en cours, on voit donc
+/// l'état d'avant) et ne détectent pas une modification faite hors de ce module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SensorSnapshot {
+    /// `false` aussi pendant la veille (voir `suspended`)
+    pub initialized: bool,
+    /// Capteur en veille (`suspend`) : `resume` ou `init` pour reprendre
+    pub suspended: bool,
+    /// `false` entre `disarm()` et `arm()` : les identifications sont ignorées
+    pub armed: bool,
+    /// Configuration verrouillée (`lock_config`)
+    pub config_locked: bool,
+    /// Dernier nombre de templates connu (`None` si jamais lu depuis l'init)
+    pub template_count: Option<u16>,
+    /// ID reconnu par la dernière identification (`None` si pas de match)
+    pub last_match: Option<u16>,
+    /// Cause du dernier non-match (`None` après un match ou avant toute identification)
+    pub last_no_match: Option<NoMatchReason>,
+    /// Position du dernier match, si le firmware la donne
+    pub last_match_geometry: Option<MatchGeometry>,
+    /// Échecs de communication consécutifs (voir `SensorCtx::comms_failures`)
+    pub comms_failures: u8,
+    pub health: HealthEstimate,
+}
+
+/// Nouvelles tentatives d'identification et budget de temps global.
+///
+/// Chaque tentative attend le doigt jusqu'à `timeout_ms` (paramètre de `check_once`),
+/// borné par le temps restant du budget. Entre deux tentatives (pas de match ou
+/// erreur de capture), pause de `backoff_ms`, elle aussi bornée par le budget.
+/// L'absence de doigt (timeout), l'annulation et `NotInitialized` arrêtent tout de
+/// suite. Quand `budget_ms` expire, on rend le meilleur résultat obtenu : un match,
+/// sinon "pas de match", sinon la dernière erreur.
+///
+/// Pire cas sans budget : `attempts * (timeout_ms + identification + backoff_ms)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdentifyPolicy {
+    /// Nombre total de tentatives (au moins 1)
+    pub attempts: u8,
+    pub backoff_ms: u32,
+    /// Durée maximale de l'identification complète, `0` = pas de budget
+    pub budget_ms: u32,
+}
+
+impl Default for IdentifyPolicy {
+    fn default() -> Self {
+        Self::SINGLE
+    }
+}
+
+impl IdentifyPolicy {
+    /// Une seule tentative, sans budget (comportement historique de `check_once`)
+    pub const SINGLE: Self = Self {
+        attempts: 1,
+        backoff_ms: 0,
+        budget_ms: 0,
+    };
+}
+
+/// Répartition du temps de la dernière opération (voir `SensorCtx::last_timing`).
+///
+/// `spi_us` cumule les transferts SPI (esp_hal.c) ; `sensor_us` est le reste :
+/// attente du doigt, traitement firmware, attente de l'IRQ, pauses entre tentatives.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct OpTiming {
+    pub spi_us: u64,
+    pub sensor_us: u64,
+    pub total_us: u64,
+}
+
+/// Étapes signalées par `SensorCtx::calibrate_with_progress`.
+#[cfg(feature = "enroll")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalibrationEvent {
+    /// Commande envoyée, le capteur calibre (quelques centaines de ms)
+    Started,
+    Finished { ok: bool, elapsed_ms: u32 },
+}
+
+/// Position du doigt reconnu, telle que le firmware l'a utilisée pour le match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MatchGeometry {
+    /// Décalage du doigt par rapport au template, en pixels capteur
+    pub dx: i16,
+    pub dy: i16,
+    /// Rotation du doigt par rapport au template, en degrés
+    pub rotation_deg: i16,
+}
+
+/// Vue typée des arguments de la dernière réponse HCP (`chain.arg`).
+///
+/// Le firmware BM-Lite ne renvoie ni score de match, ni qualité d'image, ni position
+/// du match : ces champs restent `None` tant qu'une version de firmware ne les
+/// fournit pas.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HcpArgView {
+    /// ARG_MATCH : réponse à une identification
+    pub matched: Option<bool>,
+    /// ARG_ID : template matché
+    pub id: Option<u16>,
+    /// ARG_COUNT : nombre de templates, échantillons restants à l'enrôlement, ...
+    pub count: Option<u32>,
+    pub score: Option<u16>,
+    pub quality: Option<u8>,
+    pub geometry: Option<MatchGeometry>,
+}
+
+/// Cause du dernier non-match, d'après le code renvoyé par le capteur.
+///
+/// Le firmware BM-Lite distingue seulement une capture inexploitable d'une
+/// comparaison négative : `PartialFinger` n'est jamais rapporté par ce firmware, et
+/// tout autre code donne `Unknown`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum NoMatchReason {
+    /// Le firmware n'a rien précisé
+    Unknown,
+    /// Image inexploitable : capteur sale, doigt humide ou sec (« nettoyer le capteur »)
+    LowQualityCapture,
+    /// Doigt trop peu posé sur le capteur (« décaler le doigt »)
+    PartialFinger,
+    /// Comparaison menée à bien, aucun template ne correspond (« non reconnu »)
+    NotRecognized,
+}
+
+impl NoMatchReason {
+    fn from_result(code: i32) -> Self {
+        match code {
+            0 => Self::NotRecognized,
+            BEP_RESULT_IMAGE_CAPTURE_ERROR => Self::LowQualityCapture,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Vue en lecture de la chaîne, pour les logs de diagnostic.
+pub(super) struct ChainInfo {
+    pub chain: *const c_void,
+    pub comm_size: usize,
+    pub pkt_buffer: *const u8,
+    pub txrx_buffer: *const u8,
+    pub pkt_size_max: u32,
+    pub has_write: bool,
+    pub has_read: bool,
+}
+
+/// Accès exclusif à un `SensorCtx`, fourni par l'intégrateur.
+///
+/// Le cœur ne dépend que de `core` + `alloc` : c'est à l'appelant de sérialiser
+/// les accès (Mutex std, section critique FreeRTOS, ...). Sous la feature `std`,
+/// une implémentation pour `std::sync::Mutex<SensorCtx>` est fournie.
+pub trait SensorLock {
+    fn with_ctx<R>(&self, f: impl FnOnce(&mut SensorCtx) -> R) -> R;
+}
+
+/// État du capteur et logique biométrique. Les buffers HCP sont alloués via `alloc`.
+///
+/// Aucun `unsafe` ici : tous les appels C passent par les wrappers de `bep`.
+///
+/// Avant `init` (ou après `deinit`), toute opération qui accède au capteur renvoie
+/// `FingerprintError::NotInitialized`, y compris `deinit` et `wipe_templates` :
+/// un `Ok` signifie toujours que l'action a eu lieu. Les réglages côté hôte
+/// (`set_*`, `arm` / `disarm`, labels, `on_match`, `snapshot`) restent utilisables.
+pub struct SensorCtx {
+    pub(super) board: Option<Board>,
+    pub(super) chain: Option<Chain>,
+    // Chaîne mise de côté pendant `suspend` (capteur tenu en reset)
+    pub(super) parked: Option<Chain>,
+    // Conservé à travers reset() pour détecter un échange de capteur
+    pub(super) unique_id: Option<[u8; UNIQUE_ID_LEN]>,
+    pub(super) log_target: &'static str,
+    pub(super) calibration: CalibrationTracker,
+    pub(super) enrolled: EnrolledCache,
+    pub(super) last_match: Option<u16>,
+    pub(super) last_no_match: Option<NoMatchReason>,
+    pub(super) last_match_geometry: Option<MatchGeometry>,
+    #[cfg(feature = "enroll")]
+    pub(super) enroll: EnrollSettings,
+    #[cfg(feature = "enroll")]
+    pub(super) auto_calibrate: bool,
+    pub(super) armed: bool,
+    pub(super) labels: IdTable<Label>,
+    pub(super) pinned: IdTable<()>,
+    pub(super) groups: BTreeMap<u16, Vec<u16>>,
+    pub(super) qualities: IdTable<u16>,
+    // CRC32 de référence des templates (voir `record_checksums`)
+    pub(super) checksums: IdTable<u32>,
+    pub(super) mirror: TemplateMirror,
+    pub(super) stats: IdTable<TemplateStats>,
+    pub(super) template_capacity: Option<u16>,
+    // Câblage du dernier `init` réussi, repris par `move_spi_host`
+    pub(super) pins: Option<PinConfig>,
+    pub(super) identify_policy: IdentifyPolicy,
+    // Nouvelles poses après un non-match (voir `set_auto_reattempt`)
+    pub(super) reattempt_count: u8,
+    pub(super) reattempt_window_ms: u32,
+    pub(super) lockout: LockoutTracker,
+    pub(super) clock: ClockSource,
+    pub(super) on_match: Option<MatchCallback>,
+    pub(super) last_timing: Option<OpTiming>,
+    // Message de la dernière erreur d'une opération mesurée (voir `diagnostics`)
+    pub(super) last_error: Option<String>,
+    // Échecs de communication consécutifs et seuil de récupération (voir `recovery`)
+    pub(super) comms_failures: u8,
+    pub(super) auto_recovery_threshold: u8,
+    pub(super) packet_trace: bool,
+    // Jeton de `lock_config`, `None` si la configuration est modifiable
+    pub(super) config_lock: Option<u64>,
+    // Stockage des métadonnées, ouvert au premier `init` (voir nvs.rs)
+    #[cfg(feature = "nvs")]
+    pub(super) nvs: Option<bep::MetaStore>,
+    #[cfg(feature = "nvs")]
+    pub(super) nvs_partition: Option<bep::NvsPartition>,
+    // CRC32 du dernier blob écrit ou relu : rien à écrire s'il n'a pas changé
+    #[cfg(feature = "nvs")]
+    pub(super) nvs_crc: Option<u32>,
+    #[cfg(feature = "nvs")]
+    pub(super) nvs_flushed_ms: u64,
+}
+
+impl Default for SensorCtx {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub(super) fn not_initialized() -> anyhow::Error {
+    FingerprintError::NotInitialized.into()
+}
+
+// Marqueur de la chaîne de version renvoyée par le bootloader (en minuscules)
+const BOOTLOADER_MARKER: &str = "boot";
+
+// Tranche d'attente firmware entre deux vérifications du CancelToken
+const WAIT_SLICE_MS: u32 = 100;
+// Identification non bloquante (timeout 0) : sonde de présence puis capture
+const PROBE_WAIT_MS: u16 = 1;
+const PRESENT_FINGER_TIMEOUT_MS: u32 = 1_000;
+
+// Timeout firmware limité à 16 bits (0 = attente infinie côté BM-Lite)
+fn wait_timeout(timeout_ms: u32) -> u16 {
+    timeout_ms.min(65_535) as u16
+}
+
+// Ok(true) si l'attente aboutit, Ok(false) sur timeout, Err sinon
+fn timeout_as_false(res: Result<()>) -> Result<bool> {
+    match res {
+        Ok(()) => Ok(true),
+        Err(e) if matches!(FingerprintError::of(&e), Some(FingerprintError::Timeout(_))) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+impl SensorCtx {
+    pub const fn new() -> Self {
+        Self {
+            board: None,
+            chain: None,
+            parked: None,
+            unique_id: None,
+            log_target: DEFAULT_LOG_TARGET,
+            calibration: CalibrationTracker::new(),
+            enrolled: EnrolledCache::new(),
+            last_match: None,
+            last_no_match: None,
+            last_match_geometry: None,
+            #[cfg(feature = "enroll")]
+            enroll: EnrollSettings::new(),
+            #[cfg(feature = "enroll")]
+            auto_calibrate: false,
+            armed: true,
+            labels: IdTable::new(),
+            pinned: IdTable::new(),
+            groups: BTreeMap::new(),
+            qualities: IdTable::new(),
+            checksums: IdTable::new(),
+            mirror: TemplateMirror::new(),
+            stats: IdTable::new(),
+            template_capacity: None,
+            pins: None,
+            identify_policy: IdentifyPolicy::SINGLE,
+            reattempt_count: 0,
+            reattempt_window_ms: 0,
+            lockout: LockoutTracker::new(),
+            clock: ClockSource::new(),
+            on_match: None,
+            last_timing: None,
+            last_error: None,
+            comms_failures: 0,
+            auto_recovery_threshold: DEFAULT_AUTO_RECOVERY_THRESHOLD,
+            packet_trace: false,
+            config_lock: None,
+            #[cfg(feature = "nvs")]
+            nvs: None,
+            #[cfg(feature = "nvs")]
+            nvs_partition: None,
+            #[cfg(feature = "nvs")]
+            nvs_crc: None,
+            #[cfg(feature = "nvs")]
+            nvs_flushed_ms: 0,
+        }
+    }
+
+    /// Change la target `log` utilisée par le module (à appeler avant `init`).
+    pub fn set_log_target(&mut self, target: &'static str) {
+        self.log_target = target;
+    }
+
+    fn set(&mut self, board: Board, chain: Chain) {
+        self.board = Some(board);
+        self.chain = Some(chain);
+    }
+
+    fn reset(&mut self) {
+        self.board = None;
+        self.chain = None;
+        self.enrolled.invalidate();
+        self.last_match = None;
+        self.last_no_match = None;
+        self.last_match_geometry = None;
+    }
+
+    fn is_set(&self) -> bool {
+        self.chain.is_some()
+    }
+
+    // Exécute `op`, mémorise sa répartition SPI / capteur pour `last_timing` et son
+    // éventuelle erreur pour `diagnostics`
+    pub(super) fn timed<T>(&mut self, op: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let start = bep::tick_us();
+        let spi_start = bep::spi_time_us();
+        let res = op(self);
+        let total_us = bep::tick_us().saturating_sub(start);
+        let spi_us = bep::spi_time_us().saturating_sub(spi_start);
+        self.last_timing = Some(OpTiming {
+            spi_us,
+            sensor_us: total_us.saturating_sub(spi_us),
+            total_us,
+        });
+        if let Err(e) = &res {
+            self.last_error = Some(e.to_string());
+        }
+        self.track_comms(&res);
+        res
+    }
+
+    /// Vide chaque transfert HCP (envoi / réception) en hexadécimal au niveau `trace`,
+    /// sous la target `fingerprint::hcp` (64 premiers octets par transfert). Pour le
+    /// débogage du protocole : réglage conservé à travers `init` / `deinit`.
+    pub fn set_packet_trace(&mut self, enabled: bool) {
+        self.packet_trace = enabled;
+        for chain in [self.chain.as_mut(), self.parked.as_mut()].into_iter().flatten() {
+            chain.set_packet_trace(enabled);
+        }
+    }
+
+    /// `true` entre `init` et `deinit`, hors veille (`suspend`).
+    pub fn is_initialized(&self) -> bool {
+        self.is_set()
+    }
+
+    /// Durées de la dernière identification, calibration ou enrôlement (réussi ou non),
+    /// `None` avant la première. Le compteur SPI est global à la plateforme.
+    pub fn last_timing(&self) -> Option<OpTiming> {
+        self.last_timing
+    }
+
+    /// Copie des métadonnées en cache (aucun accès capteur).
+    pub fn snapshot(&self) -> SensorSnapshot {
+        SensorSnapshot {
+            initialized: self.is_set(),
+            suspended: self.parked.is_some(),
+            config_locked: self.config_lock.is_some(),
+            armed: self.armed,
+            template_count: self.enrolled.count(),
+            last_match: self.last_match,
+            last_no_match: self.last_no_match,
+            last_match_geometry: self.last_match_geometry,
+            comms_failures: self.comms_failures,
+            health: self.calibration.estimate(),
+        }
+    }
+
+    // Lit l'identifiant unique du capteur et le compare à celui mémorisé
+    pub(super) fn check_identity(&mut self) -> Result<ConnectStatus> {
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+        let id = chain.unique_id()?;
+
+        let status = match self.unique_id {
+            None => ConnectStatus::FirstSeen,
+            Some(prev) if prev == id => ConnectStatus::SameSensor,
+            Some(_) => ConnectStatus::SensorChanged,
+        };
+        if status == ConnectStatus::SensorChanged {
+            fp_warn!(target: self.log_target, "BM-Lite: capteur remplacé (unique id = {:02x?})", id);
+            // Les IDs ne désignent plus les mêmes doigts
+            self.labels.clear();
+            self.pinned.clear();
+            self.groups.clear();
+            self.qualities.clear();
+            self.checksums.clear();
+            self.stats.clear();
+            self.template_capacity = None;
+            self.enrolled.invalidate();
+        }
+        self.unique_id = Some(id);
+        Ok(status)
+    }
+}
+
+// ======================================================
+// 5) Opérations capteur
+// ======================================================
+
+impl SensorCtx {
+    /// Initialise le capteur avec le câblage `PinConfig::esp32s3_devkit()`.
+    pub fn init(&mut self) -> Result<ConnectStatus> {
+        self.init_with_pins(&PinConfig::esp32s3_devkit())
+    }
+
+    /// Initialise le capteur avec un câblage spécifique (preset éventuellement ajusté).
+    pub fn init_with_pins(&mut self, pins: &PinConfig) -> Result<ConnectStatus> {
+        Ok(self.init_with_report(pins)?.status)
+    }
+
+    /// Comme `init_with_pins`, et renvoie le résumé du capteur connecté (firmware,
+    /// identifiant, templates, capacités). Déjà initialisé : `SameSensor`, et le
+    /// résumé est reconstruit (une seule lecture, la version firmware).
+    pub fn init_with_report(&mut self, pins: &PinConfig) -> Result<InitReport> {
+        self.resume_if_suspended()?;
+        if self.is_set() {
+            return Ok(self.init_report(ConnectStatus::SameSensor));
+        }
+        #[cfg(feature = "nvs")]
+        self.open_nvs();
+
+        let (board, mut chain) = bep::init_board(pins)?;
+        self.pins = Some(*pins);
+        chain.set_packet_trace(self.packet_trace);
+        let info = chain.info();
+        self.set(board, chain);
+
+        fp_info!(target: self.log_target, "sizeof(HCP_comm_t) = {}", info.comm_size);
+        fp_info!(target: self.log_target, "chain ptr      = {:p}", info.chain);
+        fp_info!(target: self.log_target, "pkt_buffer     = {:p}", info.pkt_buffer);
+        fp_info!(target: self.log_target, "txrx_buffer    = {:p}", info.txrx_buffer);
+        fp_info!(target: self.log_target, "pkt_size_max   = {}", info.pkt_size_max);
+        fp_info!(target: self.log_target, "After platform_init:");
+        fp_info!(target: self.log_target, "write set = {}", info.has_write);
+        fp_info!(target: self.log_target, "read set  = {}", info.has_read);
+
+        fp_info!(target: self.log_target, "Calibration du capteur...");
+        //self.calibrate()?;
+
+        if self.in_bootloader() {
+            fp_warn!(target: self.log_target, "BM-Lite: en mode bootloader, mise à jour firmware en attente");
+            return Ok(self.init_report(ConnectStatus::InBootloader));
+        }
+        #[cfg(feature = "enroll")]
+        self.abort_stale_enroll();
+
+        // Non bloquant : un firmware sans unique id ne doit pas empêcher l'init
+        let status = self.check_identity().unwrap_or_else(|e| {
+            fp_warn!(target: self.log_target, "BM-Lite: lecture unique id impossible: {e}");
+            ConnectStatus::FirstSeen
+        });
+
+        self.max_template_capacity()?;
+        // Non bloquant non plus : le cache sera relu à la première lecture
+        if let Err(e) = self.refresh_cache() {
+            fp_warn!(target: self.log_target, "BM-Lite: lecture des templates impossible: {e}");
+        }
+
+        let report = self.init_report(status);
+        fp_info!(
+            target: self.log_target,
+            "BM-Lite: init OK (firmware {}, templates {})",
+            report.firmware.as_deref().unwrap_or("?"),
+            report.template_count.map_or_else(|| "?".to_string(), |n| n.to_string())
+        );
+        Ok(report)
+    }
+
+    // Le protocole HCP n'a pas de commande d'état du bootloader, mais celui-ci répond
+    // à CMD_INFO et s'annonce dans sa chaîne de version. Sans version lisible, pas de
+    // conclusion : l'init continue et les commandes suivantes diront le reste.
+    fn in_bootloader(&mut self) -> bool {
+        let Some(chain) = self.chain.as_mut() else {
+            return false;
+        };
+        chain
+            .firmware_version()
+            .is_ok_and(|version| version.to_ascii_lowercase().contains(BOOTLOADER_MARKER))
+    }
+
+    // Rassemble le résumé d'init : seule la version firmware est lue sur le capteur
+    fn init_report(&mut self, status: ConnectStatus) -> InitReport {
+        let firmware = self.chain.as_mut().map(|chain| chain.firmware_version());
+        let firmware = match firmware {
+            Some(Ok(version)) => Some(version),
+            Some(Err(e)) => {
+                fp_warn!(target: self.log_target, "BM-Lite: lecture version firmware impossible: {e}");
+                None
+            }
+            None => None,
+        };
+        InitReport {
+            status,
+            firmware,
+            hardware_id: self.unique_id,
+            template_count: self.enrolled.count(),
+            capabilities: Capabilities {
+                template_capacity: self.template_capacity.unwrap_or(DEFAULT_TEMPLATE_CAPACITY),
+                enroll: cfg!(feature = "enroll"),
+                match_score: false,
+                match_geometry: false,
+                finger_coverage: false,
+                enroll_orientations: false,
+                enroll_area_novelty: false,
+            },
+        }
+    }
+
+    /// Libère le bus SPI, les GPIO et les buffers alloués par `init`.
+    ///
+    /// Si la plateforme refuse (erreur ESP-IDF), le contexte reste initialisé et rien
+    /// n'est libéré : l'appel peut être retenté. L'identifiant matériel est conservé
+    /// pour détecter un échange de capteur au prochain `init`.
+    pub fn deinit(&mut self) -> Result<()> {
+        self.check_unlocked("deinit")?;
+        if let Some(chain) = self.parked.take() {
+            self.chain = Some(chain);
+        }
+        let (Some(board), Some(chain)) = (self.board.take(), self.chain.take()) else {
+            return Err(not_initialized());
+        };
+        #[cfg(feature = "nvs")]
+        self.flush_nvs_logged();
+        if let Err((board, chain, e)) = bep::deinit_board(board, chain) {
+            self.set(board, chain);
+            return Err(e);
+        }
+        self.reset();
+        fp_info!(target: self.log_target, "BM-Lite: deinit OK");
+        Ok(())
+    }
+
+    /// Récupération la plus légère : réinitialise l'état HCP côté hôte (paquet en
+    /// cours, buffers), vide les réponses restées en attente, puis vérifie le lien par
+    /// un aller-retour sans effet (lecture de l'identifiant unique).
+    ///
+    /// Escalade si le lien ne répond toujours pas : `reconnect` (reset matériel),
+    /// puis `deinit` + `init`.
+    pub fn reset_hcp(&mut self) -> Result<()> {
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+        chain.reset_link();
+        let id = chain.unique_id()?;
+        if self.unique_id.is_some_and(|prev| prev != id) {
+            fp_warn!(target: self.log_target, "BM-Lite: identifiant différent après reset HCP ({:02x?})", id);
+        }
+        fp_info!(target: self.log_target, "BM-Lite: lien HCP resynchronisé");
+        Ok(())
+    }
+
+    pub fn reconnect(&mut self) -> Result<ConnectStatus> {
+        if self.parked.is_some() {
+            return Err(not_initialized());
+        }
+        let board = self.board.as_mut().ok_or_else(not_initialized)?;
+        bep::hw_reset(board);
+        self.check_identity()
+    }
+
+    /// Déplace le capteur sur le contrôleur SPI `host` (matériel reconfigurable) :
+    /// `deinit` puis `init` avec le même câblage. Les templates (flash BM-Lite) et les
+    /// réglages du contexte sont conservés ; le statut dit si c'est le même capteur.
+    ///
+    /// ESP-IDF refuse un bus déjà initialisé par un autre pilote : le capteur est alors
+    /// réinitialisé sur l'ancien contrôleur et l'erreur de `host` est renvoyée.
+    pub fn move_spi_host(&mut self, host: SpiHost) -> Result<ConnectStatus> {
+        self.check_unlocked("move_spi_host")?;
+        if self.parked.is_some() || !self.is_set() {
+            return Err(not_initialized());
+        }
+        let old = self.pins.ok_or_else(not_initialized)?;
+        if old.spi_host == host {
+            return Ok(ConnectStatus::SameSensor);
+        }
+
+        self.deinit()?;
+        match self.init_with_pins(&old.with_spi_host(host)) {
+            Ok(status) => {
+                fp_info!(target: self.log_target, "BM-Lite: déplacé de {:?} vers {host:?}", old.spi_host);
+                Ok(status)
+            }
+            // Init interrompue après l'ouverture du bus : le capteur est déjà sur `host`
+            Err(e) if self.is_set() => Err(e),
+            Err(e) => {
+                fp_warn!(target: self.log_target, "BM-Lite: {host:?} indisponible ({e}), retour sur {:?}", old.spi_host);
+                if let Err(back) = self.init_with_pins(&old) {
+                    fp_error!(target: self.log_target, "BM-Lite: réinit sur {:?} impossible: {back}", old.spi_host);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Cherche le câblage du capteur parmi `presets` (outil commun à plusieurs
+    /// révisions de carte) : init avec chacun, dans l'ordre, jusqu'à ce que le capteur
+    /// réponde (lecture de la version firmware). Renvoie ce preset, avec lequel le
+    /// capteur reste initialisé.
+    ///
+    /// À appeler avant toute init. Chaque essai raté est entièrement défait (bus SPI,
+    /// GPIO, allocations) avant le suivant ; si aucun preset ne répond, l'erreur du
+    /// dernier essai est renvoyée.
+    pub fn auto_detect(&mut self, presets: &[PinConfig]) -> Result<PinConfig> {
+        self.check_unlocked("auto_detect")?;
+        if self.is_set() || self.parked.is_some() {
+            return Err(anyhow!("auto_detect: sensor already initialized"));
+        }
+        let mut last_err = anyhow!("invalid auto_detect: no pin preset");
+        for (i, pins) in presets.iter().enumerate() {
+            let found = self.init_with_pins(pins).and_then(|_| {
+                let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+                chain.firmware_version()
+            });
+            match found {
+                Ok(version) => {
+                    fp_info!(target: self.log_target, "BM-Lite: trouvé avec le preset {i} (firmware {version})");
+                    return Ok(*pins);
+                }
+                Err(e) => {
+                    fp_warn!(target: self.log_target, "BM-Lite: pas de réponse avec le preset {i}: {e}");
+                    // Init allée jusqu'au bus : on le rend avant le preset suivant
+                    if self.is_set() {
+                        self.deinit()?;
+                    }
+                    last_err = e;
+                }
+            }
+        }
+        Err(last_err)
+    }
+
+    /// Identifiant matériel du BM-Lite (12 octets, lu via HCP à chaque appel).
+    ///
+    /// Distinct de la version firmware : il ne change pas avec une mise à jour.
+    /// FPC le présente comme unique par module, sans garantie contractuelle
+    /// documentée ; pour une liaison licence / anti-clonage, le combiner avec un
+    /// identifiant côté hôte plutôt que s'y fier seul.
+    pub fn hardware_id(&mut self) -> Result<[u8; HARDWARE_ID_LEN]> {
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+        let id = chain.unique_id()?;
+        self.unique_id = Some(id);
+        Ok(id)
+    }
+
+    /// Calibre le capteur (données stockées en flash côté BM-Lite, actives après reset)
+    /// et enregistre le résultat pour `sensor_health`.
+    ///
+    /// Pas d'export / import possible : le firmware n'accepte que calibrer ou effacer
+    /// (CMD_STORAGE_CALIBRATION + ARG_NONE / ARG_DELETE), sans téléchargement des
+    /// données. Elles survivent en revanche aux resets et coupures : inutile de
+    /// recalibrer au boot ni après `reconnect`.
+    #[cfg(feature = "enroll")]
+    pub fn calibrate(&mut self) -> Result<()> {
+        self.calibrate_with_progress(|_| {})
+    }
+
+    /// Comme `calibrate`, en signalant le début et la fin à `progress` (assistant
+    /// d'installation, pour que l'interface ne paraisse pas figée).
+    ///
+    /// Le firmware calibre en une seule commande bloquante, sans état intermédiaire :
+    /// pas d'étape ni de pourcentage, seulement `Started` puis `Finished`. Le callback
+    /// s'exécute sous le verrou du capteur (mêmes règles que `on_match`).
+    #[cfg(feature = "enroll")]
+    pub fn calibrate_with_progress(&mut self, progress: impl FnMut(CalibrationEvent)) -> Result<()> {
+        self.check_unlocked("calibrate")?;
+        self.run_calibrate(progress)
+    }
+
+    // Sans contrôle du verrou : aussi utilisé par les calibrations automatiques,
+    // réglées avant `lock_config`
+    #[cfg(feature = "enroll")]
+    pub(super) fn run_calibrate(&mut self, mut progress: impl FnMut(CalibrationEvent)) -> Result<()> {
+        if self.chain.is_none() {
+            return Err(not_initialized());
+        }
+
+        progress(CalibrationEvent::Started);
+        let res = self.timed(|ctx| ctx.chain.as_mut().ok_or_else(not_initialized)?.sensor_calibrate());
+        let elapsed_ms = self.last_timing.map_or(0, |t| (t.total_us / 1000) as u32);
+
+        self.calibration.record(elapsed_ms, res.is_ok());
+        fp_info!(target: self.log_target, "Calibration: {} en {elapsed_ms} ms", if res.is_ok() { "OK" } else { "échec" });
+        progress(CalibrationEvent::Finished { ok: res.is_ok(), elapsed_ms });
+        res
+    }
+
+    // Calibration puis reset : les données ne sont actives qu'après redémarrage du BM-Lite
+    #[cfg(feature = "enroll")]
+    pub(super) fn calibrate_and_reset(&mut self) -> Result<()> {
+        self.run_calibrate(|_| {})?;
+        let board = self.board.as_mut().ok_or_else(not_initialized)?;
+        bep::hw_reset(board);
+        Ok(())
+    }
+
+    /// Sur `FingerprintError::NotCalibrated` pendant une identification (capteur neuf),
+    /// attend le retrait du doigt, calibre, redémarre le capteur et retente une fois.
+    /// Désactivé par défaut : la calibration écrit en flash BM-Lite.
+    #[cfg(feature = "enroll")]
+    pub fn set_auto_calibrate(&mut self, enabled: bool) -> Result<()> {
+        self.check_unlocked("set_auto_calibrate")?;
+        self.auto_calibrate = enabled;
+        Ok(())
+    }
+
+    /// Estimation de santé à partir de l'historique des calibrations depuis le boot.
+    pub fn sensor_health(&self) -> HealthEstimate {
+        self.calibration.estimate()
+    }
+
+    /// Température du capteur en °C, si le firmware la rapporte.
+    ///
+    /// Le protocole HCP du BM-Lite n'a aucun argument de température (ni dans
+    /// CMD_INFO, ni dans CMD_SENSOR) : renvoie toujours `Ok(None)` sur ce firmware,
+    /// sans commande envoyée. La décision de recalibrer reste fondée sur la dérive de
+    /// durée (`sensor_health`).
+    pub fn temperature(&mut self) -> Result<Option<f32>> {
+        if self.chain.is_none() {
+            return Err(not_initialized());
+        }
+        Ok(None)
+    }
+
+    /// Dimensions et résolution des images du capteur, pour afficher une capture à
+    /// l'échelle.
+    ///
+    /// HCP définit bien ARG_WIDTH / ARG_HEIGHT / ARG_DPI, mais aucune commande du
+    /// BM-Lite ne les renvoie, et le firmware ne remonte pas les images (CMD_IMAGE
+    /// n'extrait qu'un template) : renvoie `FingerprintError::Unsupported` plutôt que
+    /// des valeurs de fiche produit qui ne correspondraient pas forcément au capteur.
+    pub fn image_geometry(&mut self) -> Result<ImageGeometry> {
+        if self.chain.is_none() {
+            return Err(not_initialized());
+        }
+        Err(FingerprintError::Unsupported("image geometry").into())
+    }
+
+    /// Nombre de templates stockés sur le capteur, depuis le cache (voir `refresh_cache`).
+    pub fn enrolled_count(&mut self) -> Result<u16> {
+        Ok(self.enrolled_ids()?.len() as u16)
+    }
+
+    /// Nombre maximal de templates stockables, mis en cache dès `init`.
+    ///
+    /// Le protocole HCP n'a pas d'argument de capacité (CMD_INFO ne renvoie que
+    /// version / unique id) : on retombe sur `DEFAULT_TEMPLATE_CAPACITY`, et on le
+    /// signale dans les logs. Le point d'entrée reste unique pour le jour où un
+    /// firmware la rapporte.
+    pub fn max_template_capacity(&mut self) -> Result<u16> {
+        if self.chain.is_none() {
+            return Err(not_initialized());
+        }
+        if let Some(capacity) = self.template_capacity {
+            return Ok(capacity);
+        }
+        fp_info!(
+            target: self.log_target,
+            "BM-Lite: capacité non rapportée par le firmware, défaut {DEFAULT_TEMPLATE_CAPACITY} templates"
+        );
+        self.template_capacity = Some(DEFAULT_TEMPLATE_CAPACITY);
+        Ok(DEFAULT_TEMPLATE_CAPACITY)
+    }
+
+    /// IDs libres, triés, parmi `1..=max_template_capacity()` (l'ID 1 est celui
+    /// d'`enroll_user`).
+    pub fn free_slots(&mut self) -> Result<Vec<u16>> {
+        let capacity = self.max_template_capacity()?;
+        let used = self.enrolled_ids()?;
+        Ok((1..=capacity).filter(|id| !used.contains(id)).collect())
+    }
+
+    /// Au moins un template enregistré (quel que soit le nombre d'utilisateurs).
+    pub fn is_user_enrolled(&mut self) -> Result<bool> {
+        Ok(self.enrolled_count()? >= 1)
+    }
+
+    /// Exactement un template enregistré (cas mono-utilisateur de la démo).
+    pub fn is_single_user(&mut self) -> Result<bool> {
+        Ok(self.enrolled_count()? == 1)
+    }
+
+    /// Supprime tous les templates sauf les épinglés (voir `pin_template`), ou
+    /// vraiment tous avec `force` (épinglage conservé pour un futur enrôlement).
+    pub fn wipe_templates(&mut self, force: bool) -> Result<RemovalReport> {
+        self.check_unlocked("wipe_templates")?;
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+        if force || self.pinned.is_empty() {
+            let removed = match self.enrolled.count() {
+                Some(count) => count,
+                None => chain.template_count()?,
+            };
+            chain.template_remove_all()?;
+            self.enrolled.clear();
+            self.mirror.clear();
+            self.labels.clear();
+            self.qualities.clear();
+            self.checksums.clear();
+            self.stats.clear();
+            return Ok(RemovalReport { removed, skipped: Vec::new() });
+        }
+        self.remove_templates_in_range(u16::MIN, u16::MAX, false)
+    }
+
+    /// Supprime les templates occupés dont l'ID est dans `[start, end]`, sauf les
+    /// épinglés si `force` est faux.
+    pub fn remove_templates_in_range(&mut self, start: u16, end: u16, force: bool) -> Result<RemovalReport> {
+        self.check_unlocked("remove_templates_in_range")?;
+        if start > end {
+            return Err(anyhow!("invalid template range [{start}, {end}]"));
+        }
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+
+        let ids = chain.template_ids()?;
+        self.enrolled.set(&ids);
+
+        let mut report = RemovalReport::default();
+        for id in ids.into_iter().filter(|id| (start..=end).contains(id)) {
+            if !force && self.pinned.contains(id) {
+                report.skipped.push(id);
+                continue;
+            }
+            chain.template_remove(id)?;
+            self.labels.remove(id);
+            self.qualities.remove(id);
+            self.checksums.remove(id);
+            self.stats.remove(id);
+            self.enrolled.remove(id);
+            self.mirror.remove(id);
+            report.removed += 1;
+        }
+
+        fp_info!(
+            target: self.log_target,
+            "Templates supprimés dans [{start}, {end}]: {} (épinglés conservés : {:?})",
+            report.removed,
+            report.skipped
+        );
+        Ok(report)
+    }
+
+    /// Copie le template `id` (format accepté par `verify_transient`, `import_all`, ...).
+    /// Servi par le miroir hôte s'il est activé (voir `enable_mirror`), capteur
+    /// initialisé comme sans miroir.
+    pub fn export_template(&mut self, id: u16) -> Result<Vec<u8>> {
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+        if let Some(data) = self.mirror.get(id) {
+            return Ok(data.to_vec());
+        }
+        chain.template_export(id)
+    }
+
+    /// Comme `export_template`, sans allocation : le template est écrit dans `buf` et
+    /// sa taille renvoyée. Erreur si `buf` est trop petit ; `MAX_TEMPLATE_LEN` octets
+    /// suffisent toujours.
+    pub fn export_template_into(&mut self, id: u16, buf: &mut [u8]) -> Result<usize> {
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+        chain.template_export_into(id, buf)
+    }
+
+    /// CRC32 des octets du template `id` tels qu'exportés par le BM-Lite.
+    ///
+    /// Stable entre deux lectures du même template (utile pour savoir s'il faut
+    /// re-sauvegarder), mais non comparable entre versions de firmware si le format change.
+    pub fn template_checksum(&mut self, id: u16) -> Result<u32> {
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+        let data = chain.template_export(id)?;
+        Ok(bep::crc32(&data))
+    }
+
+    /// Capture le doigt posé et le compare au seul `template` (format de
+    /// `template_export`, par ex. lu sur une carte à puce). Le template n'occupe
+    /// jamais de slot : il est chargé en RAM BM-Lite puis effacé, même sur erreur.
+    ///
+    /// `Ok(false)` = pas de match ; l'absence de doigt donne `FingerprintError::Timeout`,
+    /// un firmware sans CMD_MATCH `FingerprintError::Unsupported`.
+    pub fn verify_transient(&mut self, template: &[u8], timeout_ms: u32) -> Result<bool> {
+        if self.chain.is_none() {
+            return Err(not_initialized());
+        }
+        self.check_lockout()?;
+        validate_template_bytes(template)?;
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+        let capture_timeout = timeout_ms.clamp(1, u32::from(u16::MAX)) as u16;
+        let matched = chain.verify_transient(template, capture_timeout)?;
+        let _ = chain.wait_finger_not_present(5000);
+        fp_info!(target: self.log_target, "Vérification par template externe : {}", if matched { "match" } else { "pas de match" });
+        self.record_access(matched);
+        Ok(matched)
+    }
+
+    /// Cause du dernier non-match, pour guider l'utilisateur (voir `NoMatchReason`).
+    /// `None` si la dernière identification a reconnu le doigt ou s'est terminée en
+    /// erreur (pas de doigt, lien).
+    pub fn last_no_match_reason(&self) -> Option<NoMatchReason> {
+        self.last_no_match
+    }
+
+    /// Position du doigt lors du dernier match (décalage, rotation), pour étudier la
+    /// façon dont les utilisateurs posent le doigt. `None` sans match, et toujours avec
+    /// le firmware BM-Lite : aucun argument HCP ne la transporte (voir `HcpArgView`).
+    pub fn last_match_geometry(&self) -> Option<MatchGeometry> {
+        self.last_match_geometry
+    }
+
+    /// Arguments de la dernière réponse HCP (match, id, compteur), à lire juste après
+    /// l'opération concernée : la commande suivante les écrase. Après `check_once`,
+    /// la dernière réponse est celle de l'attente de retrait du doigt.
+    pub fn read_arg(&mut self) -> Result<HcpArgView> {
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+        Ok(chain.read_arg())
+    }
+
+    /// Attend qu'un doigt soit posé. Retourne `false` si le délai expire.
+    pub fn wait_for_finger(&mut self, timeout_ms: u32) -> Result<bool> {
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+        timeout_as_false(chain.wait_finger_present(wait_timeout(timeout_ms)))
+    }
+
+    /// Comme `wait_for_finger`, mais interruptible depuis une autre tâche : l'attente
+    /// est découpée en tranches de `WAIT_SLICE_MS` et le jeton est vérifié entre
+    /// deux tranches. Le délai total est mesuré sur l'horloge système (`0` = infini).
+    ///
+    /// Retourne `Err(FingerprintError::Cancelled)` si le jeton est annulé. Aucune
+    /// interruption GPIO n'est utilisée (détection doigt par le firmware) : rien à
+    /// désinscrire au retour, les appels répétés n'accumulent pas de handlers.
+    pub fn wait_for_finger_cancellable(&mut self, timeout_ms: u32, cancel: &CancelToken) -> Result<bool> {
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+        let deadline = self.clock.now_ms() + u64::from(timeout_ms);
+
+        loop {
+            if cancel.is_cancelled() {
+                return Err(FingerprintError::Cancelled.into());
+            }
+            let slice = if timeout_ms == 0 {
+                WAIT_SLICE_MS
+            } else {
+                let left = deadline.saturating_sub(self.clock.now_ms());
+                if left == 0 {
+                    return Ok(false);
+                }
+                (left as u32).min(WAIT_SLICE_MS)
+            };
+            if timeout_as_false(chain.wait_finger_present(wait_timeout(slice)))? {
+                return Ok(true);
+            }
+        }
+    }
+
+    /// Attend que le doigt soit retiré (détection firmware finger-up), par ex. pour
+    /// exiger un nouvel appui après un match. Retourne `false` si le doigt reste posé.
+    pub fn wait_for_finger_release(&mut self, timeout_ms: u32) -> Result<bool> {
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+        timeout_as_false(chain.wait_finger_not_present(wait_timeout(timeout_ms)))
+    }
+
+    /// Réactive l'identification après `disarm()`.
+    pub fn arm(&mut self) {
+        if !self.armed {
+            self.armed = true;
+            fp_info!(target: self.log_target, "Identification réarmée");
+        }
+    }
+
+    /// Suspend l'identification sans toucher au capteur (fenêtre de maintenance) :
+    /// `check_once` et `identify_candidates` répondent "pas de match" immédiatement.
+    /// Moins coûteux qu'un deinit / init, l'état du capteur est conservé.
+    pub fn disarm(&mut self) {
+        if self.armed {
+            self.armed = false;
+            fp_info!(target: self.log_target, "Identification désarmée");
+        }
+    }
+
+    pub fn is_armed(&self) -> bool {
+        self.armed
+    }
+
+    /// Politique de nouvelles tentatives de `check_once` et variantes.
+    pub fn set_identify_policy(&mut self, policy: IdentifyPolicy) -> Result<()> {
+        self.check_unlocked("set_identify_policy")?;
+        self.identify_policy = IdentifyPolicy {
+            attempts: policy.attempts.max(1),
+            ..policy
+        };
+        Ok(())
+    }
+
+    /// Après un non-match, laisse `count` nouvelles poses du doigt avant de conclure :
+    /// chacune attend le retrait du doigt, puis une nouvelle pose pendant au plus
+    /// `window_ms`. À la différence de `IdentifyPolicy` (tentatives au même timeout,
+    /// erreurs de lien comprises), seule une nouvelle pose rapide est attendue.
+    ///
+    /// Coût en latence : un match obtenu à la première pose n'est pas retardé, mais un
+    /// vrai non-match n'est rapporté qu'après jusqu'à `count * (window_ms + durée d'une
+    /// identification)` de plus, plus le retrait du doigt. Avec un `budget_ms` dans
+    /// `IdentifyPolicy`, les nouvelles poses puisent dans le même budget : chaque
+    /// fenêtre est réduite au temps restant et plus aucune n'est ouverte une fois le
+    /// budget épuisé, la garantie de latence tient donc toujours. Sans effet avec un
+    /// timeout nul (`check_once(0)`). `count = 0` désactive (défaut).
+    pub fn set_auto_reattempt(&mut self, count: u8, window_ms: u32) -> Result<()> {
+        self.check_unlocked("set_auto_reattempt")?;
+        if count > 0 && !(1..=MAX_IDENTIFY_TIMEOUT_MS).contains(&window_ms) {
+            return Err(anyhow!("invalid reattempt window {window_ms} ms"));
+        }
+        self.reattempt_count = count;
+        self.reattempt_window_ms = window_ms;
+        Ok(())
+    }
+
+    /// Sensibilité de détection du doigt (IRQ et déclenchement de capture), de
+    /// `MIN_DETECT_SENSITIVITY` (peu sensible, milieu humide) à `MAX_DETECT_SENSITIVITY`
+    /// (doigts secs, appui léger).
+    ///
+    /// Le protocole HCP du BM-Lite n'a pas ce réglage (CMD_SETTINGS sans argument de
+    /// seuil de détection) : une fois `level` validé, renvoie
+    /// `FingerprintError::Unsupported` et le seuil reste celui du firmware.
+    pub fn set_detect_sensitivity(&mut self, level: u8) -> Result<()> {
+        self.check_unlocked("set_detect_sensitivity")?;
+        if self.chain.is_none() {
+            return Err(not_initialized());
+        }
+        if !(MIN_DETECT_SENSITIVITY..=MAX_DETECT_SENSITIVITY).contains(&level) {
+            return Err(anyhow!("invalid detect sensitivity {level}"));
+        }
+        Err(FingerprintError::Unsupported("detect sensitivity").into())
+    }
+
+    /// Enregistre l'action appelée avec l'ID matché à chaque identification réussie
+    /// (`check_once`, `identify_candidates`, ...). Remplace l'action précédente.
+    ///
+    /// Réentrance : le callback s'exécute sous le verrou du capteur, avant le retour
+    /// de l'opération. Il ne doit pas rappeler l'API `fingerprint` (interblocage sur
+    /// le Mutex global) et doit rester court : piloter un relais, poster dans une file.
+    pub fn on_match(&mut self, callback: MatchCallback) {
+        self.on_match = Some(callback);
+    }
+
+    /// Retire l'action enregistrée par `on_match`.
+    pub fn clear_on_match(&mut self) {
+        self.on_match = None;
+    }
+
+    /// Enregistrerait l'action appelée avec la couverture du capteur par le doigt
+    /// (en %) pendant `capture` / `wait_for_finger`, pour guider la pose en temps réel
+    /// (« appuie plus », « centre ton doigt »).
+    ///
+    /// Le firmware BM-Lite ne fournit aucune couverture : ni en continu pendant la
+    /// capture, ni une valeur après (aucun argument HCP, `HcpArgView::quality` reste
+    /// `None`). `Capabilities::finger_coverage` vaut `false` et l'appel renvoie
+    /// `FingerprintError::Unsupported` sans garder `callback`. Après coup,
+    /// `NoMatchReason::LowQualityCapture` signale seulement une image inexploitable.
+    pub fn on_coverage(&mut self, _callback: CoverageCallback) -> Result<()> {
+        if self.chain.is_none() {
+            return Err(not_initialized());
+        }
+        Err(FingerprintError::Unsupported("finger coverage").into())
+    }
+
+    /// Attend un doigt jusqu'à `timeout_ms` puis l'identifie. `Ok(false)` si le doigt
+    /// n'est pas reconnu, `Err(FingerprintError::Timeout)` si aucun doigt n'est posé.
+    ///
+    /// `0` = non bloquant : une seule tentative (sans `IdentifyPolicy`), seulement si
+    /// un doigt est déjà posé, sinon `Ok(false)` immédiatement. Les valeurs au-delà
+    /// de `MAX_IDENTIFY_TIMEOUT_MS` sont ramenées à ce maximum.
+    pub fn check_once(&mut self, timeout_ms: u32) -> Result<bool> {
+        Ok(self.identify(timeout_ms)?.is_some())
+    }
+
+    /// Comme `check_once`, annulable depuis une autre tâche via `cancel`
+    /// (`Err(FingerprintError::Cancelled)`).
+    ///
+    /// Granularité : le jeton est vérifié toutes les `WAIT_SLICE_MS` (100 ms) pendant
+    /// l'attente du doigt, puis juste avant l'identification. La séquence capture +
+    /// extraction + identification (quelques centaines de ms) n'est pas interruptible.
+    pub fn check_once_cancellable(&mut self, timeout_ms: u32, cancel: &CancelToken) -> Result<bool> {
+        Ok(self.identify_with(timeout_ms, Some(cancel))?.is_some())
+    }
+
+    /// Identifie chaque doigt posé jusqu'à un match, dont l'ID est renvoyé, ou jusqu'à
+    /// ce que `stop` passe à vrai (`Err(FingerprintError::Cancelled)`, `stop` n'est
+    /// pas remis à faux). Chaque doigt passe par `check_once` (`IdentifyPolicy`,
+    /// calibration automatique, `on_match`) ; un doigt non reconnu est ignoré.
+    ///
+    /// Entre deux doigts, l'attente est faite par le firmware (IRQ de détection, ESP
+    /// au repos) en tranches de `WAIT_SLICE_MS` (100 ms), `stop` étant vérifié entre
+    /// deux tranches puis juste avant l'identification : même granularité que
+    /// `check_once_cancellable`. Une identification commencée, retentatives de la
+    /// politique comprises, va à son terme. Désarmé, la boucle attend sans identifier.
+    /// Toute erreur capteur autre qu'un timeout l'interrompt.
+    pub fn identify_until_match(&mut self, stop: &AtomicBool) -> Result<u16> {
+        if self.chain.is_none() {
+            return Err(not_initialized());
+        }
+
+        loop {
+            if stop.load(Ordering::Acquire) {
+                return Err(FingerprintError::Cancelled.into());
+            }
+            if !self.armed {
+                self.clock.sleep_ms(WAIT_SLICE_MS);
+                continue;
+            }
+            let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+            if !timeout_as_false(chain.wait_finger_present(wait_timeout(WAIT_SLICE_MS)))?
+                || stop.load(Ordering::Acquire)
+            {
+                continue;
+            }
+
+            match self.identify(PRESENT_FINGER_TIMEOUT_MS) {
+                Ok(Some(tid)) => return Ok(tid),
+                Ok(None) => {}
+                // Doigt retiré avant la capture : on reprend l'attente
+                Err(e) if matches!(FingerprintError::of(&e), Some(FingerprintError::Timeout(_))) => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Candidats `(template_id, score)` triés par score décroissant, au plus `n`.
+    ///
+    /// Limitation : le firmware BM-Lite ne renvoie que le meilleur match (ARG_MATCH +
+    /// ARG_ID) et aucun score. Le vecteur contient donc au plus un élément, avec un
+    /// score à 0 ; il est vide si aucun template ne correspond. Avec `n = 0`, rien
+    /// n'est capturé (ni callback `on_match`, ni accès compté pour le verrouillage).
+    pub fn identify_candidates(&mut self, n: usize, timeout_ms: u32) -> Result<Vec<(u16, u16)>> {
+        let mut candidates = Vec::new();
+        if n == 0 {
+            return if self.chain.is_some() { Ok(candidates) } else { Err(not_initialized()) };
+        }
+        if let Some(tid) = self.identify(timeout_ms)? {
+            candidates.push((tid, 0));
+        }
+        candidates.sort_by_key(|&(_, score)| core::cmp::Reverse(score));
+        candidates.truncate(n);
+        Ok(candidates)
+    }
+
+    // Attente doigt -> identification -> attente retrait. Retourne l'ID matché.
+    pub(super) fn identify(&mut self, timeout_ms: u32) -> Result<Option<u16>> {
+        self.identify_with(timeout_ms, None)
+    }
+
+    // Avec un jeton : attente découpée et annulable, puis dernière vérification
+    // avant la capture + identification (non interruptible côté firmware).
+    fn identify_with(&mut self, timeout_ms: u32, cancel: Option<&CancelToken>) -> Result<Option<u16>> {
+        if self.chain.is_none() {
+            return Err(not_initialized());
+        }
+        if !self.armed {
+            return Ok(None);
+        }
+        self.check_lockout()?;
+
+        if timeout_ms > MAX_IDENTIFY_TIMEOUT_MS {
+            fp_warn!(target: self.log_target, "Timeout {timeout_ms} ms ramené à {MAX_IDENTIFY_TIMEOUT_MS} ms");
+        }
+        let timeout_ms = timeout_ms.min(MAX_IDENTIFY_TIMEOUT_MS);
+        if timeout_ms == 0 {
+            return self.identify_now();
+        }
+
+        // Callback hors mesure : son temps n'est pas celui du capteur
+        #[allow(unused_mut)]
+        let mut matched = self.timed(|ctx| ctx.identify_retry(timeout_ms, cancel));
+
+        #[cfg(feature = "enroll")]
+        if self.auto_calibrate {
+            if let Err(e) = &matched {
+                if let Some(FingerprintError::NotCalibrated(_)) = FingerprintError::of(e) {
+                    fp_warn!(target: self.log_target, "Capteur non calibré : lève ton doigt, calibration...");
+                    self.wait_for_finger_release(5000)?;
+                    self.calibrate_and_reset()?;
+                    matched = self.timed(|ctx| ctx.identify_retry(timeout_ms, cancel));
+                }
+            }
+        }
+
+        if let Ok(tid) = &matched {
+            self.record_access(tid.is_some());
+        }
+        if let (Ok(Some(tid)), Some(callback)) = (&matched, self.on_match.as_mut()) {
+            callback(*tid);
+        }
+        matched
+    }
+
+    // Timeout 0 : une seule tentative, uniquement si un doigt est déjà posé
+    fn identify_now(&mut self) -> Result<Option<u16>> {
+        let matched = self.timed(|ctx| {
+            let chain = ctx.chain.as_mut().ok_or_else(not_initialized)?;
+            if !timeout_as_false(chain.wait_finger_present(PROBE_WAIT_MS))? {
+                return Ok(None);
+            }
+            ctx.identify_attempt(PRESENT_FINGER_TIMEOUT_MS, None)
+        });
+        if let Ok(tid) = &matched {
+            self.record_access(tid.is_some());
+        }
+        if let (Ok(Some(tid)), Some(callback)) = (&matched, self.on_match.as_mut()) {
+            callback(*tid);
+        }
+        matched
+    }
+
+    // Boucle de tentatives selon `identify_policy`
+    fn identify_retry(&mut self, timeout_ms: u32, cancel: Option<&CancelToken>) -> Result<Option<u16>> {
+        let policy = self.identify_policy;
+        let start = self.clock.now_ms();
+        // Temps restant du budget à l'instant `now` (None = pas de budget)
+        let budget = u64::from(policy.budget_ms);
+        let left = |now: u64| (budget > 0).then(|| budget.saturating_sub(now - start) as u32);
+
+        let mut no_match = false;
+        let mut last_err = None;
+        for attempt in 1..=policy.attempts {
+            let timeout = match left(self.clock.now_ms()) {
+                Some(0) => break,
+                Some(rest) => timeout_ms.min(rest),
+                None => timeout_ms,
+            };
+
+            match self.identify_attempt(timeout, cancel) {
+                Ok(Some(tid)) => return Ok(Some(tid)),
+                Ok(None) => no_match = true,
+                Err(e) => {
+                    let fatal = matches!(
+                        FingerprintError::of(&e),
+                        Some(
+                            FingerprintError::Timeout(_)
+                                | FingerprintError::Cancelled
+                                | FingerprintError::NotInitialized
+                                | FingerprintError::NotCalibrated(_)
+                        )
+                    );
+                    if fatal {
+                        return if no_match { Ok(None) } else { Err(e) };
+                    }
+                    fp_warn!(target: self.log_target, "Identification {attempt}/{}: {e}", policy.attempts);
+                    last_err = Some(e);
+                }
+            }
+
+            if attempt < policy.attempts && policy.backoff_ms > 0 {
+                let pause = left(self.clock.now_ms()).map_or(policy.backoff_ms, |rest| rest.min(policy.backoff_ms));
+                self.clock.sleep_ms(pause);
+            }
+        }
+
+        match last_err {
+            Some(e) if !no_match => Err(e),
+            _ if no_match => self.reattempt(left, cancel),
+            _ => Ok(None),
+        }
+    }
+
+    // Nouvelles poses après un non-match (`set_auto_reattempt`), fenêtres bornées par le
+    // budget restant (`left`, voir `identify_retry`). Sans nouvelle pose dans la
+    // fenêtre ou budget épuisé, le non-match est rapporté ; annulation et capteur
+    // indisponible remontent en erreur.
+    fn reattempt(&mut self, left: impl Fn(u64) -> Option<u32>, cancel: Option<&CancelToken>) -> Result<Option<u16>> {
+        for attempt in 1..=self.reattempt_count {
+            let window = match left(self.clock.now_ms()) {
+                Some(0) => break,
+                Some(rest) => self.reattempt_window_ms.min(rest),
+                None => self.reattempt_window_ms,
+            };
+
+            match self.identify_attempt(window, cancel) {
+                Ok(Some(tid)) => {
+                    fp_info!(target: self.log_target, "Reconnu à la nouvelle pose {attempt}");
+                    return Ok(Some(tid));
+                }
+                Ok(None) => {}
+                Err(e) => match FingerprintError::of(&e) {
+                    Some(FingerprintError::Timeout(_)) => break,
+                    Some(
+                        FingerprintError::Cancelled
+                        | FingerprintError::NotInitialized
+                        | FingerprintError::NotCalibrated(_),
+                    ) => return Err(e),
+                    _ => fp_warn!(target: self.log_target, "Nouvelle pose {attempt}/{}: {e}", self.reattempt_count),
+                },
+            }
+        }
+        Ok(None)
+    }
+
+    // Une tentative : attente doigt -> identification -> attente retrait
+    pub(super) fn identify_attempt(&mut self, timeout_ms: u32, cancel: Option<&CancelToken>) -> Result<Option<u16>> {
+        // 1) Attendre que le doigt soit posé
+        match cancel {
+            Some(cancel) => {
+                if !self.wait_for_finger_cancellable(timeout_ms, cancel)? {
+                    return Err(FingerprintError::Timeout("sensor_wait_finger_present").into());
+                }
+                if cancel.is_cancelled() {
+                    return Err(FingerprintError::Cancelled.into());
+                }
+            }
+            None => {
+                let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+                chain.wait_finger_present(wait_timeout(timeout_ms))?;
+            }
+        }
+
+        // 2) Identifier
+        let chain = self.chain.as_mut().ok_or_else(not_initialized)?;
+        let matched = chain.identify_finger(timeout_ms)?;
+        // Avant l'attente de retrait, qui écrase le code de la réponse
+        let no_match = matched.is_none().then(|| NoMatchReason::from_result(chain.last_result()));
+        let geometry = matched.and_then(|_| chain.read_arg().geometry);
+
+        // 3) Attendre que le doigt soit retiré 
+        let _ = chain.wait_finger_not_present(5000);
+
+        // 4) Refuser un ID hors des templates enrôlés
+        if let Some(tid) = matched {
+            self.check_matched_id(tid)?;
+        }
+
+        self.last_match = matched;
+        self.last_no_match = no_match;
+        self.last_match_geometry = geometry;
+        if let Some(tid) = matched {
+            fp_info!(target: self.log_target, "Matched template id = {}", tid);
+            self.record_match(tid);
+        }
+
+        Ok(matched)
+    }
+}
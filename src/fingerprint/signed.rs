@@ -0,0 +1,67 @@
+#![forbid(unsafe_code)]
+
+// Matchs signés pour une serrure en réseau (feature `crypto`) : le backend envoie un
+// nonce, le module identifie le doigt et renvoie un jeton HMAC-SHA256 qui lie l'ID
+// reconnu à ce nonce. Le backend, qui partage la clé, vérifie le MAC et n'accepte
+// chaque nonce qu'une fois : un jeton rejoué ou forgé sans la clé est refusé.
+//
+// Message authentifié (le backend le reconstruit à l'identique) :
+//   SIGNED_MATCH_CONTEXT || id (u16 LE) || timestamp (u64 LE) || nonce
+//
+// `timestamp` est l'heure système en secondes Unix, 0 si elle n'est pas réglée
+// (SNTP, RTC) : la fraîcheur repose alors sur le seul nonce. La clé vit chez
+// l'appelant ; le module ne la conserve pas.
+
+use anyhow::{anyhow, Result};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use super::sensor::SensorCtx;
+
+/// Préfixe du message authentifié (change si le format évolue).
+pub const SIGNED_MATCH_CONTEXT: &[u8] = b"fingerprint-v3/match/v1";
+
+/// Match authentifié par `check_once_signed`, à transmettre au backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignedMatch {
+    pub template_id: u16,
+    /// Secondes Unix au moment du match, 0 si l'horloge n'est pas réglée
+    pub timestamp: u64,
+    /// HMAC-SHA256 de (contexte, ID, timestamp, nonce)
+    pub mac: [u8; 32],
+}
+
+impl SensorCtx {
+    /// Comme `check_once`, mais un match renvoie un `SignedMatch` lié à `nonce` et
+    /// authentifié avec `key`. `Ok(None)` si le doigt n'est pas reconnu.
+    pub fn check_once_signed(
+        &mut self,
+        timeout_ms: u32,
+        nonce: &[u8],
+        key: &[u8],
+    ) -> Result<Option<SignedMatch>> {
+        if nonce.is_empty() {
+            return Err(anyhow!("invalid nonce: empty"));
+        }
+        if key.is_empty() {
+            return Err(anyhow!("invalid HMAC key: empty"));
+        }
+
+        let Some(template_id) = self.identify(timeout_ms)? else {
+            return Ok(None);
+        };
+        let timestamp = self.clock.unix_time_s().unwrap_or(0);
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(key).map_err(|_| anyhow!("invalid HMAC key"))?;
+        mac.update(SIGNED_MATCH_CONTEXT);
+        mac.update(&template_id.to_le_bytes());
+        mac.update(&timestamp.to_le_bytes());
+        mac.update(nonce);
+
+        Ok(Some(SignedMatch {
+            template_id,
+            timestamp,
+            mac: mac.finalize().into_bytes().into(),
+        }))
+    }
+}
@@ -0,0 +1,550 @@
+#![forbid(unsafe_code)]
+// Le simulateur garde toute l'API du backend, même sans la feature `enroll`
+#![cfg_attr(not(feature = "enroll"), allow(dead_code))]
+
+// Backend simulé (feature `sim`) : remplace `bep` avec la même API, sans matériel.
+//
+// L'état du "capteur" (templates en flash, doigt posé) vit dans un singleton
+// `DEVICE`, indépendant du `SensorCtx` : comme la flash d'un vrai BM-Lite, il survit
+// aux reset / reconnexion et peut être préparé avant `init()` :
+//
+//     fingerprint::sim_seed_template(1);               // template 1 déjà enrôlé
+//     fingerprint::sim_set_finger(SimFinger::Template(1));
+//
+// Enrôlement et identification sont déterministes : l'enrôlement demande
+// `ENROLL_SAMPLES` captures et réussit toujours, l'identification renvoie le
+// template désigné par `SimFinger`. `sim_fail_next` simule une erreur de lien sur
+// une commande, pour voir le comportement du module à chaque étape d'une séquence.
+
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use anyhow::{anyhow, Result};
+use core::ptr;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+
+use super::error::{
+    FingerprintError, BEP_RESULT_ID_NOT_FOUND, BEP_RESULT_IMAGE_CAPTURE_ERROR, BEP_RESULT_INVALID_CALIBRATION,
+    BEP_RESULT_IO_ERROR,
+};
+use super::pins::PinConfig;
+use super::sensor::{ChainInfo, HcpArgView};
+
+pub(super) const UNIQUE_ID_LEN: usize = 12;
+
+// Codes fpc_bep_result_t renvoyés par le faux firmware
+const BEP_RESULT_NO_RESOURCE: i32 = -7;
+
+// Captures nécessaires pour un enrôlement (le vrai BM-Lite en demande en général 3 à 6)
+const ENROLL_SAMPLES: u32 = 3;
+// Attente simulée d'un doigt qui ne vient jamais, quand le timeout est infini (0)
+const ABSENT_FINGER_WAIT_MS: u64 = 1000;
+
+const SIM_UNIQUE_ID: [u8; UNIQUE_ID_LEN] = *b"SIM-BM-LITE\0";
+const SIM_VERSION: &str = "sim";
+
+/// Doigt présenté au capteur simulé lors de la prochaine capture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimFinger {
+    /// Un doigt qui correspond au plus petit ID enregistré (défaut)
+    Enrolled,
+    /// Un doigt qui correspond au template `id` (pas de match si absent du stockage)
+    Template(u16),
+    /// Un doigt inconnu : capture OK, pas de match
+    Unknown,
+    /// Un doigt mal capturé (sale, humide) : pas de match, `IMAGE_CAPTURE_ERROR`
+    Smudged,
+    /// Aucun doigt : les attentes / captures expirent
+    Absent,
+}
+
+/// Commande qui échoue au prochain appel (voir `sim_fail_next`), sur une erreur de
+/// lien (`IO_ERROR`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimFault {
+    Calibrate,
+    EnrollStart,
+    EnrollAdd,
+    /// Le template est écrit, puis la réponse se perd : sauvegarde orpheline
+    TemplateSave,
+    TemplateIds,
+    Identify,
+    /// L'identification répond `INVALID_CALIBRATION` (capteur jamais calibré) au lieu
+    /// d'une erreur de lien
+    Uncalibrated,
+}
+
+struct SimDevice {
+    templates: BTreeMap<u16, Vec<u8>>,
+    // Template en RAM (fin d'enrôlement ou import), perdu au reset
+    ram: Option<Vec<u8>>,
+    enroll_remaining: Option<u32>,
+    finger: SimFinger,
+    last_arg: HcpArgView,
+    last_result: i32,
+    fault: Option<SimFault>,
+    // Maintenu en reset (veille) : ligne RST basse
+    reset_held: bool,
+    #[cfg(feature = "crypto")]
+    rng: u64,
+}
+
+static DEVICE: Mutex<SimDevice> = Mutex::new(SimDevice {
+    templates: BTreeMap::new(),
+    ram: None,
+    enroll_remaining: None,
+    finger: SimFinger::Enrolled,
+    last_arg: HcpArgView {
+        matched: None,
+        id: None,
+        count: None,
+        score: None,
+        quality: None,
+        geometry: None,
+    },
+    last_result: 0,
+    fault: None,
+    reset_held: false,
+    #[cfg(feature = "crypto")]
+    rng: 0x9E37_79B9_7F4A_7C15,
+});
+
+fn device() -> MutexGuard<'static, SimDevice> {
+    DEVICE.lock().unwrap()
+}
+
+impl SimDevice {
+    // Consomme la panne programmée si elle vise `fault`
+    fn fail(&mut self, fault: SimFault, what: &'static str) -> Result<()> {
+        self.fail_with(fault, what, BEP_RESULT_IO_ERROR)
+    }
+
+    // Idem, avec le code `fpc_bep_result_t` renvoyé
+    fn fail_with(&mut self, fault: SimFault, what: &'static str, code: i32) -> Result<()> {
+        if self.fault != Some(fault) {
+            return Ok(());
+        }
+        self.fault = None;
+        Err(FingerprintError::from_code(what, code).into())
+    }
+}
+
+// Contenu factice mais stable d'un template : permet checksum / export / import
+fn fake_template(seed: u16) -> Vec<u8> {
+    (0..64u16)
+        .map(|i| (seed.wrapping_mul(31).wrapping_add(i) & 0xFF) as u8)
+        .collect()
+}
+
+/// Enregistre un template `id` dans la flash simulée (remplace l'existant).
+pub fn sim_seed_template(id: u16) {
+    device().templates.insert(id, fake_template(id));
+}
+
+/// Choisit le doigt présenté aux prochaines captures.
+pub fn sim_set_finger(finger: SimFinger) {
+    device().finger = finger;
+}
+
+/// Laisse un enrôlement ouvert, comme un reboot de l'hôte en plein enrôlement avec
+/// une broche RST sans effet : `init_board` simulé ne fait pas de reset.
+pub fn sim_wedge_enroll() {
+    device().enroll_remaining = Some(ENROLL_SAMPLES - 1);
+}
+
+/// Fait échouer le prochain appel de la commande `fault` (une seule fois).
+pub fn sim_fail_next(fault: SimFault) {
+    device().fault = Some(fault);
+}
+
+/// Vide la flash simulée.
+pub fn sim_clear_templates() {
+    device().templates.clear();
+}
+
+// ======================================================
+// Plateforme simulée
+// ======================================================
+
+pub(super) struct Board {
+    _private: (),
+}
+
+pub(super) struct Chain {
+    _private: (),
+}
+
+pub(super) fn init_board(pins: &PinConfig) -> Result<(Board, Chain)> {
+    pins.validate()?;
+    Ok((Board { _private: () }, Chain { _private: () }))
+}
+
+pub(super) fn deinit_board(
+    _board: Board,
+    _chain: Chain,
+) -> core::result::Result<(), (Board, Chain, anyhow::Error)> {
+    Ok(())
+}
+
+pub(super) fn hw_reset(_board: &mut Board) {
+    let mut dev = device();
+    dev.ram = None;
+    dev.enroll_remaining = None;
+    dev.reset_held = false;
+}
+
+pub(super) fn hold_reset(board: &mut Board, held: bool) {
+    if held {
+        hw_reset(board);
+    }
+    device().reset_held = held;
+}
+
+/// IRQ toujours basse (aucune réponse en attente hors d'une commande), RST basse
+/// seulement en veille.
+pub(super) fn control_lines(_board: &Board) -> (bool, bool) {
+    (false, !device().reset_held)
+}
+
+/// Attente bloquante (thread::sleep).
+pub(super) fn busy_wait(ms: u32) {
+    std::thread::sleep(Duration::from_millis(ms.into()));
+}
+
+/// CRC32 IEEE (même résultat que fpc_crc sur la cible).
+pub(super) fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in data {
+        crc ^= u32::from(b);
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+/// Pseudo-aléa xorshift : suffisant pour tester le format, PAS pour protéger des données.
+#[cfg(feature = "crypto")]
+pub(super) fn fill_random(buf: &mut [u8]) {
+    let mut dev = device();
+    for b in buf {
+        dev.rng ^= dev.rng << 13;
+        dev.rng ^= dev.rng >> 7;
+        dev.rng ^= dev.rng << 17;
+        *b = dev.rng as u8;
+    }
+}
+
+fn start() -> Instant {
+    static START: OnceLock<Instant> = OnceLock::new();
+    *START.get_or_init(Instant::now)
+}
+
+/// Temps écoulé depuis le premier appel, en ms.
+pub(super) fn tick_ms() -> u64 {
+    start().elapsed().as_millis() as u64
+}
+
+/// Temps écoulé depuis le premier appel, en µs.
+pub(super) fn tick_us() -> u64 {
+    start().elapsed().as_micros() as u64
+}
+
+/// Pas de bus SPI simulé : tout le temps d'une opération est compté côté capteur.
+pub(super) fn spi_time_us() -> u64 {
+    0
+}
+
+/// Pas de partition à partager : la NVS simulée est un blob en mémoire.
+#[cfg(feature = "nvs")]
+#[derive(Clone)]
+pub(super) struct NvsPartition;
+
+// NVS simulée, indépendante du `SensorCtx` comme la vraie (survit à deinit / init)
+#[cfg(feature = "nvs")]
+static NVS: Mutex<Option<Vec<u8>>> = Mutex::new(None);
+
+/// Blob de métadonnées dans la NVS simulée.
+#[cfg(feature = "nvs")]
+pub(super) struct MetaStore;
+
+#[cfg(feature = "nvs")]
+impl MetaStore {
+    pub(super) fn open(_partition: Option<NvsPartition>) -> Result<Self> {
+        Ok(Self)
+    }
+
+    pub(super) fn load(&mut self) -> Result<Option<Vec<u8>>> {
+        Ok(NVS.lock().unwrap().clone())
+    }
+
+    pub(super) fn save(&mut self, blob: &[u8]) -> Result<()> {
+        *NVS.lock().unwrap() = Some(blob.to_vec());
+        Ok(())
+    }
+}
+
+/// Blob de métadonnées actuellement dans la NVS simulée.
+#[cfg(feature = "nvs")]
+pub fn sim_nvs_blob() -> Option<Vec<u8>> {
+    NVS.lock().unwrap().clone()
+}
+
+/// Remplace le blob de la NVS simulée (`None` : NVS vierge), relu au prochain
+/// premier `init` d'un contexte.
+#[cfg(feature = "nvs")]
+pub fn sim_set_nvs_blob(blob: Option<Vec<u8>>) {
+    *NVS.lock().unwrap() = blob;
+}
+
+// Un doigt absent fait expirer l'attente après `timeout_ms` (borné si infini)
+fn finger_down(timeout_ms: u32, what: &'static str) -> Result<()> {
+    if device().finger != SimFinger::Absent {
+        return Ok(());
+    }
+    let wait = if timeout_ms == 0 {
+        ABSENT_FINGER_WAIT_MS
+    } else {
+        timeout_ms.into()
+    };
+    std::thread::sleep(Duration::from_millis(wait));
+    Err(FingerprintError::Timeout(what).into())
+}
+
+fn not_found(what: &'static str) -> anyhow::Error {
+    FingerprintError::from_code(what, BEP_RESULT_ID_NOT_FOUND).into()
+}
+
+// ======================================================
+// Commandes BM-Lite simulées
+// ======================================================
+
+impl Chain {
+    /// Seule l'identification renseigne un code simulé.
+    pub fn last_result(&self) -> i32 {
+        device().last_result
+    }
+
+    pub fn read_arg(&mut self) -> HcpArgView {
+        device().last_arg
+    }
+
+    /// Pas de lien HCP simulé : seul le dernier argument est oublié.
+    pub fn reset_link(&mut self) {
+        device().last_arg = HcpArgView::default();
+    }
+
+    /// Pas de transport simulé : rien à tracer.
+    pub fn set_packet_trace(&mut self, _enabled: bool) {}
+
+    pub fn info(&self) -> ChainInfo {
+        ChainInfo {
+            chain: ptr::null(),
+            comm_size: 0,
+            pkt_buffer: ptr::null(),
+            txrx_buffer: ptr::null(),
+            pkt_size_max: 0,
+            has_write: true,
+            has_read: true,
+        }
+    }
+
+    /// Capture + identification. Retourne l'ID matché, `None` si aucun template ne correspond.
+    pub fn identify_finger(&mut self, timeout_ms: u32) -> Result<Option<u16>> {
+        finger_down(timeout_ms, "bep_identify_finger")?;
+
+        let mut dev = device();
+        dev.fail(SimFault::Identify, "bep_identify_finger")?;
+        dev.fail_with(SimFault::Uncalibrated, "bep_identify_finger", BEP_RESULT_INVALID_CALIBRATION)?;
+        let matched = match dev.finger {
+            SimFinger::Enrolled => dev.templates.keys().next().copied(),
+            SimFinger::Template(id) => dev.templates.contains_key(&id).then_some(id),
+            SimFinger::Unknown | SimFinger::Smudged | SimFinger::Absent => None,
+        };
+        dev.last_result = if dev.finger == SimFinger::Smudged { BEP_RESULT_IMAGE_CAPTURE_ERROR } else { 0 };
+        dev.last_arg = HcpArgView {
+            matched: Some(matched.is_some()),
+            id: matched,
+            ..HcpArgView::default()
+        };
+        Ok(matched)
+    }
+
+    /// Capture d'une image (attend le doigt jusqu'à `timeout_ms`).
+    pub fn capture(&mut self, timeout_ms: u16) -> Result<()> {
+        finger_down(timeout_ms.into(), "bep_capture")
+    }
+
+    pub fn enroll_start(&mut self) -> Result<()> {
+        let mut dev = device();
+        dev.fail(SimFault::EnrollStart, "enroll start")?;
+        // Un enrôlement déjà ouvert bloque le suivant tant qu'il n'est pas fermé
+        if dev.enroll_remaining.is_some() {
+            return Err(FingerprintError::from_code("enroll start", BEP_RESULT_NO_RESOURCE).into());
+        }
+        dev.enroll_remaining = Some(ENROLL_SAMPLES);
+        Ok(())
+    }
+
+    /// Ajoute l'image capturée à l'enrôlement. Retourne le nombre d'échantillons restants.
+    pub fn enroll_add(&mut self) -> Result<u32> {
+        let mut dev = device();
+        dev.fail(SimFault::EnrollAdd, "enroll add")?;
+        let Some(remaining) = dev.enroll_remaining else {
+            return Err(FingerprintError::from_code("enroll add", BEP_RESULT_NO_RESOURCE).into());
+        };
+        let remaining = remaining.saturating_sub(1);
+        dev.enroll_remaining = Some(remaining);
+        if remaining == 0 {
+            // Graine dérivée du contenu courant : deux enrôlements donnent deux templates
+            let seed = dev.templates.len() as u16 + 1;
+            dev.ram = Some(fake_template(seed));
+        }
+        dev.last_arg = HcpArgView {
+            count: Some(remaining),
+            ..HcpArgView::default()
+        };
+        Ok(remaining)
+    }
+
+    pub fn enroll_finish(&mut self) -> Result<()> {
+        if device().enroll_remaining.take().is_none() {
+            return Err(FingerprintError::from_code("enroll finish", BEP_RESULT_NO_RESOURCE).into());
+        }
+        Ok(())
+    }
+
+    pub fn enroll_abort(&mut self) -> Result<()> {
+        let res = self.enroll_finish();
+        device().ram = None;
+        res
+    }
+
+    pub fn sensor_calibrate(&mut self) -> Result<()> {
+        device().fail(SimFault::Calibrate, "bep_sensor_calibrate")?;
+        busy_wait(20);
+        Ok(())
+    }
+
+    pub fn sw_reset(&mut self) -> Result<()> {
+        let mut dev = device();
+        dev.ram = None;
+        dev.enroll_remaining = None;
+        Ok(())
+    }
+
+    pub fn unique_id(&mut self) -> Result<[u8; UNIQUE_ID_LEN]> {
+        Ok(SIM_UNIQUE_ID)
+    }
+
+    pub fn firmware_version(&mut self) -> Result<String> {
+        Ok(SIM_VERSION.into())
+    }
+
+    pub fn template_count(&mut self) -> Result<u16> {
+        let mut dev = device();
+        let count = dev.templates.len() as u16;
+        dev.last_arg = HcpArgView {
+            count: Some(count.into()),
+            ..HcpArgView::default()
+        };
+        Ok(count)
+    }
+
+    /// IDs occupés, triés.
+    pub fn template_ids(&mut self) -> Result<Vec<u16>> {
+        let mut dev = device();
+        dev.fail(SimFault::TemplateIds, "bep_template_get_ids")?;
+        Ok(dev.templates.keys().copied().collect())
+    }
+
+    pub fn template_export(&mut self, id: u16) -> Result<Vec<u8>> {
+        device()
+            .templates
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| not_found("bep_template_load_storage"))
+    }
+
+    /// Comme `template_export`, dans `buf` ; erreur si `buf` est trop petit.
+    pub fn template_export_into(&mut self, id: u16, buf: &mut [u8]) -> Result<usize> {
+        let data = self.template_export(id)?;
+        if data.len() > buf.len() {
+            return Err(anyhow!("export buffer too small: template is {} bytes, buffer {}", data.len(), buf.len()));
+        }
+        buf[..data.len()].copy_from_slice(&data);
+        Ok(data.len())
+    }
+
+    /// Charge `data` en RAM puis le sauvegarde en flash sous `id`.
+    pub fn template_import(&mut self, id: u16, data: &[u8]) -> Result<()> {
+        device().templates.insert(id, data.to_vec());
+        Ok(())
+    }
+
+    /// Match si le doigt présenté est celui dont `data` est le template.
+    pub fn verify_transient(&mut self, data: &[u8], timeout_ms: u16) -> Result<bool> {
+        finger_down(timeout_ms.into(), "bep_capture")?;
+
+        let mut dev = device();
+        let matched = match dev.finger {
+            SimFinger::Enrolled => dev.templates.values().next().is_some_and(|t| t == data),
+            SimFinger::Template(id) => {
+                fake_template(id) == data || dev.templates.get(&id).is_some_and(|t| t == data)
+            }
+            SimFinger::Unknown | SimFinger::Smudged | SimFinger::Absent => false,
+        };
+        dev.ram = None;
+        dev.last_arg = HcpArgView {
+            matched: Some(matched),
+            ..HcpArgView::default()
+        };
+        Ok(matched)
+    }
+
+    /// Template extrait d'une capture : celui du doigt simulé (jamais le même pour un
+    /// doigt inconnu, qui ne se matche donc pas lui-même).
+    pub fn probe_export(&mut self, timeout_ms: u16) -> Result<Vec<u8>> {
+        finger_down(timeout_ms.into(), "bep_capture")?;
+
+        let dev = device();
+        match dev.finger {
+            SimFinger::Enrolled => Ok(dev.templates.values().next().cloned().unwrap_or_else(|| fake_template(0))),
+            SimFinger::Template(id) => Ok(dev.templates.get(&id).cloned().unwrap_or_else(|| fake_template(id))),
+            SimFinger::Unknown => Ok(fake_template(u16::MAX)),
+            SimFinger::Smudged | SimFinger::Absent => {
+                Err(FingerprintError::from_code("image extract", BEP_RESULT_IMAGE_CAPTURE_ERROR).into())
+            }
+        }
+    }
+
+    pub fn template_save(&mut self, id: u16) -> Result<()> {
+        let mut dev = device();
+        let data = dev.ram.take().ok_or_else(|| {
+            FingerprintError::from_code("bep_template_save", BEP_RESULT_NO_RESOURCE)
+        })?;
+        dev.templates.insert(id, data);
+        dev.fail(SimFault::TemplateSave, "bep_template_save")
+    }
+
+    pub fn template_remove(&mut self, id: u16) -> Result<()> {
+        device()
+            .templates
+            .remove(&id)
+            .map(|_| ())
+            .ok_or_else(|| not_found("bep_template_remove"))
+    }
+
+    pub fn template_remove_all(&mut self) -> Result<()> {
+        device().templates.clear();
+        Ok(())
+    }
+
+    pub fn wait_finger_present(&mut self, timeout_ms: u16) -> Result<()> {
+        finger_down(timeout_ms.into(), "sensor_wait_finger_present")
+    }
+
+    /// Le doigt simulé est toujours retiré immédiatement.
+    pub fn wait_finger_not_present(&mut self, _timeout_ms: u16) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+#![forbid(unsafe_code)]
+
+// Statistiques d'usage par template (date d'enrôlement, dernier match, nombre de
+// matchs), pour l'audit et le nettoyage des enrôlements qui ne servent plus.
+//
+// Tenues à jour par l'enrôlement et l'identification, elles sont gardées côté hôte
+// comme les qualités ; sans la feature `nvs`, `all_template_stats` et
+// `set_template_stats` servent à les sauvegarder et les restaurer. Les dates sont
+// des secondes Unix lues sur l'horloge du contexte (`SensorCtx::set_clock`, heure
+// système par défaut) : tant qu'elle n'a pas été mise à l'heure (SNTP, RTC), elles
+// restent à `None` plutôt que de dater depuis le boot.
+
+use alloc::vec::Vec;
+
+use super::sensor::SensorCtx;
+
+/// Usage d'un template (voir `SensorCtx::template_stats`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TemplateStats {
+    /// Enrôlement (ou dernière mise à jour), en secondes Unix
+    pub enrolled_at: Option<u64>,
+    /// Dernière identification réussie, en secondes Unix
+    pub last_matched_at: Option<u64>,
+    pub match_count: u32,
+}
+
+impl SensorCtx {
+    /// Usage du template `id`, `None` si inconnu (template importé, enrôlé avant le
+    /// boot sans restauration, ...). Un template inconnu qui matche est suivi à partir
+    /// de ce match, sans date d'enrôlement.
+    pub fn template_stats(&self, id: u16) -> Option<TemplateStats> {
+        self.stats.get(id)
+    }
+
+    /// Restaure des statistiques sauvegardées. Ignorées si `id` est nouveau et que
+    /// `MAX_TRACKED_TEMPLATES` IDs sont déjà suivis.
+    pub fn set_template_stats(&mut self, id: u16, stats: TemplateStats) {
+        if !self.stats.insert(id, stats) {
+            fp_warn!(target: self.log_target, "Template {id}: statistiques ignorées, table pleine");
+        }
+    }
+
+    /// Toutes les statistiques connues, triées par ID.
+    pub fn all_template_stats(&self) -> Vec<(u16, TemplateStats)> {
+        self.stats.iter().collect()
+    }
+
+    // Template (ré)écrit par un enrôlement : compteurs remis à zéro
+    pub(super) fn record_enrolled(&mut self, id: u16) {
+        self.stats.insert(
+            id,
+            TemplateStats {
+                enrolled_at: self.clock.unix_time_s(),
+                ..TemplateStats::default()
+            },
+        );
+    }
+
+    // Table pleine : un template inconnu qui matche n'est pas suivi
+    pub(super) fn record_match(&mut self, id: u16) {
+        let Some(stats) = self.stats.get_or_default(id) else {
+            return;
+        };
+        stats.last_matched_at = self.clock.unix_time_s().or(stats.last_matched_at);
+        stats.match_count = stats.match_count.saturating_add(1);
+    }
+}
@@ -0,0 +1,108 @@
+#![forbid(unsafe_code)]
+
+// Table à capacité fixe, indexée par ID de template, pour les métadonnées hôte
+// (labels, épinglage, qualités, CRC32, statistiques) : tableaux de taille connue à la
+// compilation, sans allocation, qui tiennent dans le `SensorCtx` lui-même (pile ou
+// `static`).
+//
+// La capacité `MAX_TRACKED_TEMPLATES` se choisit par feature ; la plus grande
+// activée l'emporte. Au-delà, un nouvel ID n'est pas suivi (`insert` renvoie faux),
+// les IDs déjà présents restent modifiables. Le miroir des templates (jusqu'à 3 Ko
+// par template) et les groupes (listes de membres) restent sur le tas.
+
+/// IDs de templates suivis au plus par les métadonnées hôte. Défaut : 32 (le
+/// BM-Lite en stocke 5 sans mise à jour firmware) ; features `max-templates-8` et
+/// `max-templates-128` pour l'ajuster. Coût : une cinquantaine d'octets par ID.
+pub const MAX_TRACKED_TEMPLATES: usize = if cfg!(feature = "max-templates-128") {
+    128
+} else if cfg!(feature = "max-templates-8") {
+    8
+} else {
+    32
+};
+
+// `ids[..len]` triés, `values[i]` associé à `ids[i]` (toujours `Some` dans `..len`)
+pub(super) struct IdTable<V: Copy> {
+    ids: [u16; MAX_TRACKED_TEMPLATES],
+    values: [Option<V>; MAX_TRACKED_TEMPLATES],
+    len: usize,
+}
+
+impl<V: Copy> IdTable<V> {
+    pub(super) const fn new() -> Self {
+        Self {
+            ids: [0; MAX_TRACKED_TEMPLATES],
+            values: [None; MAX_TRACKED_TEMPLATES],
+            len: 0,
+        }
+    }
+
+    fn find(&self, id: u16) -> Result<usize, usize> {
+        self.ids[..self.len].binary_search(&id)
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub(super) fn contains(&self, id: u16) -> bool {
+        self.find(id).is_ok()
+    }
+
+    pub(super) fn get(&self, id: u16) -> Option<V> {
+        self.find(id).ok().and_then(|i| self.values[i])
+    }
+
+    // Faux si `id` est nouveau et la table pleine : rien n'est écrit
+    pub(super) fn insert(&mut self, id: u16, value: V) -> bool {
+        match self.find(id) {
+            Ok(i) => self.values[i] = Some(value),
+            Err(_) if self.len == MAX_TRACKED_TEMPLATES => return false,
+            Err(i) => {
+                self.ids.copy_within(i..self.len, i + 1);
+                self.values.copy_within(i..self.len, i + 1);
+                self.ids[i] = id;
+                self.values[i] = Some(value);
+                self.len += 1;
+            }
+        }
+        true
+    }
+
+    pub(super) fn remove(&mut self, id: u16) {
+        if let Ok(i) = self.find(id) {
+            self.ids.copy_within(i + 1..self.len, i);
+            self.values.copy_within(i + 1..self.len, i);
+            self.len -= 1;
+            self.values[self.len] = None;
+        }
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.values = [None; MAX_TRACKED_TEMPLATES];
+        self.len = 0;
+    }
+
+    // Entrées triées par ID
+    pub(super) fn iter(&self) -> impl Iterator<Item = (u16, V)> + '_ {
+        self.ids[..self.len]
+            .iter()
+            .zip(&self.values[..self.len])
+            .filter_map(|(&id, value)| value.map(|v| (id, v)))
+    }
+}
+
+impl<V: Copy + Default> IdTable<V> {
+    // Valeur de `id`, créée par défaut si absente ; `None` si la table est pleine
+    pub(super) fn get_or_default(&mut self, id: u16) -> Option<&mut V> {
+        if !self.contains(id) && !self.insert(id, V::default()) {
+            return None;
+        }
+        let i = self.find(id).ok()?;
+        self.values[i].as_mut()
+    }
+}
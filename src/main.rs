@@ -1,11 +1,19 @@
+extern crate alloc;
+
 use std::{thread, time::Duration};
+#[cfg(target_os = "espidf")]
 use esp_idf_svc::log::EspLogger;
 
 mod fingerprint;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    esp_idf_svc::sys::link_patches();
-    EspLogger::initialize_default();
+    #[cfg(target_os = "espidf")]
+    {
+        esp_idf_svc::sys::link_patches();
+        EspLogger::initialize_default();
+    }
+    #[cfg(not(target_os = "espidf"))]
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
 
     log::info!("=== Test BM-Lite ===");
 
@@ -13,18 +21,33 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // toujours enrôler 4 fois au démarrage (à chaque lancement)
     // ✅ Toujours enrôler 1 fois au démarrage (à chaque lancement)
     log::info!("On va enrôler un doigt (1 fois)...");
-    fingerprint::wipe_templates()?;          // optionnel mais conseillé si tu veux repartir à zéro
+    fingerprint::wipe_templates(false)?;     // optionnel mais conseillé si tu veux repartir à zéro
     fingerprint::enroll_user()?;             // enrôlement une fois
     log::info!("✅ Enrôlement terminé");
 
+    // réaction au match, appelée par la bibliothèque
+    fingerprint::on_match(Box::new(|id| log::info!("✅ Doigt reconnu (template {id})")));
+
     //boucle de vérification
     loop {
         log::info!("Pose ton doigt sur le capteur...");
 
         match fingerprint::check_once(5_000) {
-            Ok(true) => log::info!("✅ Doigt reconnu"),
+            Ok(true) => {} // réaction dans on_match
             Ok(false) => log::warn!("❌ Doigt non reconnu"),
-            Err(e) => log::error!("Erreur BM-Lite: {e}"),
+            Err(e) => match fingerprint::RecoveryAction::of(&e) {
+                fingerprint::RecoveryAction::Transient => log::warn!("Erreur BM-Lite: {e}"),
+                fingerprint::RecoveryAction::Recoverable => {
+                    log::error!("Erreur BM-Lite: {e}, reconnexion...");
+                    if let Err(e) = fingerprint::reconnect() {
+                        log::error!("Reconnexion impossible: {e}");
+                    }
+                }
+                fingerprint::RecoveryAction::Fatal => {
+                    log::error!("Erreur BM-Lite fatale: {e}");
+                    return Err(e.into());
+                }
+            },
         } // toujours enroller 5 fois au démarrage
 
         thread::sleep(Duration::from_millis(500));
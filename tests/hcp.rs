@@ -0,0 +1,207 @@
+// Couche lien HCP (`hcp_tiny.c`) sur un transport simulé, sans matériel.
+//
+// build.rs compile `hcp_tiny.c` pour l'hôte (`libhcp_host.a`) ; ce test remplace la
+// HAL SPI par des callbacks `read` / `write` qui servent une réponse préparée ici.
+//
+//     cargo test --features sim --target x86_64-unknown-linux-gnu --test hcp
+//
+// Le SPI est cadencé par l'hôte : chaque `read` rend la taille demandée ou échoue.
+// Un paquet plus grand que le MTU arrive donc en plusieurs trames, chacune lue en
+// deux fois (en-tête, puis contenu + CRC), et `bmlite_receive` le réassemble.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+// Valeurs de fpc_bep_types.h / fpc_hcp_common.h / hcp_tiny.h
+const FPC_BEP_RESULT_OK: i32 = 0;
+const FPC_BEP_RESULT_IO_ERROR: i32 = -8;
+const FPC_BEP_RESULT_TIMEOUT: i32 = -11;
+const CMD_TEMPLATE: u16 = 0x0006;
+const ARG_DATA: u16 = 0x100A;
+const MTU: usize = 256;
+const FPC_BEP_ACK: u32 = 0x7f01_ff7f;
+// Contenu utile d'une trame : MTU - en-têtes lien (4) et transport (6) - CRC (4)
+const APP_MTU: usize = MTU - 6 - 8;
+
+#[repr(C)]
+struct HcpArg {
+    size: u32,
+    data: *mut u8,
+}
+
+// Miroir de `HCP_comm_t`
+#[repr(C)]
+struct HcpComm {
+    write: extern "C" fn(u16, *const u8, u32) -> i32,
+    read: extern "C" fn(u16, *mut u8, u32) -> i32,
+    phy_rx_timeout: u32,
+    pkt_buffer: *mut u8,
+    pkt_size_max: u32,
+    pkt_size: u32,
+    txrx_buffer: *mut u8,
+    arg: HcpArg,
+    bep_result: i32,
+}
+
+#[link(name = "hcp_host", kind = "static")]
+extern "C" {
+    fn bmlite_receive(hcp_comm: *mut HcpComm) -> i32;
+    fn bmlite_get_arg(hcp_comm: *mut HcpComm, arg_type: u16) -> i32;
+    fn fpc_crc(crc: u32, buf: *const u8, size: u32) -> u32;
+}
+
+// Transport simulé : octets envoyés par le capteur, tailles lues, ACK reçus
+#[derive(Default)]
+struct Transport {
+    rx: VecDeque<u8>,
+    reads: Vec<u16>,
+    acks: usize,
+}
+
+thread_local! {
+    // Un par thread de test : les callbacks C n'ont pas de contexte
+    static TRANSPORT: RefCell<Transport> = RefCell::default();
+}
+
+// Lecture SPI : la taille demandée, ou un timeout si le capteur n'a pas tout envoyé
+extern "C" fn mock_read(size: u16, data: *mut u8, _timeout: u32) -> i32 {
+    TRANSPORT.with_borrow_mut(|t| {
+        t.reads.push(size);
+        let size = usize::from(size);
+        if t.rx.len() < size {
+            t.rx.clear();
+            return FPC_BEP_RESULT_TIMEOUT;
+        }
+        let bytes: Vec<u8> = t.rx.drain(..size).collect();
+        unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), data, size) };
+        FPC_BEP_RESULT_OK
+    })
+}
+
+extern "C" fn mock_write(size: u16, data: *const u8, _timeout: u32) -> i32 {
+    let bytes = unsafe { std::slice::from_raw_parts(data, usize::from(size)) };
+    if bytes == FPC_BEP_ACK.to_le_bytes() {
+        TRANSPORT.with_borrow_mut(|t| t.acks += 1);
+    }
+    FPC_BEP_RESULT_OK
+}
+
+// Paquet applicatif : commande, puis un argument `ARG_DATA`
+fn packet(data: &[u8]) -> Vec<u8> {
+    let mut pkt = Vec::new();
+    for v in [CMD_TEMPLATE, 1, ARG_DATA, data.len() as u16] {
+        pkt.extend_from_slice(&v.to_le_bytes());
+    }
+    pkt.extend_from_slice(data);
+    pkt
+}
+
+// Découpe `pkt` en trames lien (canal, taille, transport, contenu, CRC)
+fn frames(pkt: &[u8]) -> Vec<Vec<u8>> {
+    let chunks: Vec<&[u8]> = pkt.chunks(APP_MTU).collect();
+    let seq_len = chunks.len() as u16;
+    chunks
+        .iter()
+        .zip(1..)
+        .map(|(chunk, seq_nr)| {
+            let t_size = chunk.len() as u16;
+            let mut frame = Vec::new();
+            for v in [0, t_size + 6, t_size, seq_nr, seq_len] {
+                frame.extend_from_slice(&v.to_le_bytes());
+            }
+            frame.extend_from_slice(chunk);
+            let crc = unsafe { fpc_crc(0, frame[4..].as_ptr(), u32::from(t_size) + 6) };
+            frame.extend_from_slice(&crc.to_le_bytes());
+            frame
+        })
+        .collect()
+}
+
+fn comm(pkt_buffer: &mut [u8], txrx_buffer: &mut [u8]) -> HcpComm {
+    HcpComm {
+        write: mock_write,
+        read: mock_read,
+        phy_rx_timeout: 100,
+        pkt_buffer: pkt_buffer.as_mut_ptr(),
+        pkt_size_max: pkt_buffer.len() as u32,
+        pkt_size: 0,
+        txrx_buffer: txrx_buffer.as_mut_ptr(),
+        arg: HcpArg {
+            size: 0,
+            data: std::ptr::null_mut(),
+        },
+        bep_result: FPC_BEP_RESULT_OK,
+    }
+}
+
+// Reçoit ce qui a été mis dans le transport ; renvoie le code et le paquet réassemblé
+fn receive(rx: Vec<u8>) -> (i32, Vec<u8>) {
+    TRANSPORT.with_borrow_mut(|t| {
+        *t = Transport {
+            rx: rx.into(),
+            ..Transport::default()
+        }
+    });
+    let mut pkt_buffer = vec![0u8; 1024];
+    let mut txrx_buffer = vec![0u8; MTU];
+    let mut comm = comm(&mut pkt_buffer, &mut txrx_buffer);
+    let result = unsafe { bmlite_receive(&mut comm) };
+    let received = pkt_buffer[..comm.pkt_size as usize].to_vec();
+    (result, received)
+}
+
+#[test]
+fn packet_split_across_reads_is_reassembled() {
+    let data: Vec<u8> = (0..400u16).map(|i| i as u8).collect();
+    let pkt = packet(&data);
+    let frames = frames(&pkt);
+    assert_eq!(frames.len(), 2);
+
+    let (result, received) = receive(frames.concat());
+    assert_eq!(result, FPC_BEP_RESULT_OK);
+    assert_eq!(received, pkt);
+
+    // Deux lectures par trame, un ACK par trame
+    let sizes: Vec<u16> = frames
+        .iter()
+        .flat_map(|f| [4, f.len() as u16 - 4])
+        .collect();
+    TRANSPORT.with_borrow(|t| {
+        assert_eq!(t.reads, sizes);
+        assert_eq!(t.acks, 2);
+    });
+
+    // L'argument est lisible d'un bloc dans le paquet réassemblé
+    let mut pkt_buffer = received;
+    let mut comm = comm(&mut pkt_buffer, &mut []);
+    comm.pkt_size = comm.pkt_size_max;
+    assert_eq!(
+        unsafe { bmlite_get_arg(&mut comm, ARG_DATA) },
+        FPC_BEP_RESULT_OK
+    );
+    let arg = unsafe { std::slice::from_raw_parts(comm.arg.data, comm.arg.size as usize) };
+    assert_eq!(arg, data);
+}
+
+#[test]
+fn truncated_frame_reports_the_failed_read() {
+    let frames = frames(&packet(&[0xA5; 400]));
+    // Seconde trame coupée au milieu de son contenu
+    let mut rx = frames.concat();
+    rx.truncate(frames[0].len() + 100);
+
+    let (result, _) = receive(rx);
+    // Le timeout de la lecture, pas un CRC faux calculé sur l'ancien contenu du buffer
+    assert_eq!(result, FPC_BEP_RESULT_TIMEOUT);
+    TRANSPORT.with_borrow(|t| assert_eq!(t.acks, 1));
+}
+
+#[test]
+fn corrupted_frame_is_rejected() {
+    let mut rx = frames(&packet(&[0x5A; 100])).concat();
+    rx[20] ^= 0x01;
+
+    let (result, _) = receive(rx);
+    assert_eq!(result, FPC_BEP_RESULT_IO_ERROR);
+    TRANSPORT.with_borrow(|t| assert_eq!(t.acks, 0));
+}
@@ -0,0 +1,8 @@
+// Stub de esp_log.h pour compiler hcp_tiny.c sur l'hôte (voir build.rs) : journaux ignorés.
+#pragma once
+
+#define ESP_LOGE(tag, ...) ((void)(tag))
+#define ESP_LOGW(tag, ...) ((void)(tag))
+#define ESP_LOGI(tag, ...) ((void)(tag))
+#define ESP_LOGD(tag, ...) ((void)(tag))
+#define ESP_LOGV(tag, ...) ((void)(tag))
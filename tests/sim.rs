@@ -0,0 +1,529 @@
+// Scénarios de bout en bout sur le capteur simulé (feature `sim`), sans matériel.
+//
+// Le crate n'a qu'un binaire (`harness = false`, sans cible lib) : ce test inclut le
+// module `fingerprint` par chemin, comme `main.rs`, et passe par `SensorCtx`.
+//
+//     cargo test --features sim --target x86_64-unknown-linux-gnu
+//     cargo test --features sim,nvs --target x86_64-unknown-linux-gnu   # + persistance NVS
+//
+// Le capteur simulé est un singleton (`DEVICE`) : chaque test le prend via `sim()`,
+// qui sérialise les tests et remet la flash (et la NVS) simulée à zéro.
+#![cfg(feature = "sim")]
+
+extern crate alloc;
+
+// Seule une partie de l'API sert ici
+#[allow(dead_code, unused_imports)]
+#[path = "../src/fingerprint/mod.rs"]
+mod fingerprint;
+
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use fingerprint::{
+    sim_clear_templates, sim_fail_next, sim_seed_template, sim_set_finger, sim_wedge_enroll,
+    CancelToken, Clock, EnrollPreparation, ExistingPolicy, FingerprintError, IdentifyPolicy,
+    LockoutPolicy, MockClock, SensorCtx, SimFault, SimFinger,
+};
+
+fn sim() -> MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+    let guard = LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    sim_clear_templates();
+    sim_set_finger(SimFinger::Enrolled);
+    #[cfg(feature = "nvs")]
+    fingerprint::sim_set_nvs_blob(None);
+    guard
+}
+
+fn ready_ctx() -> SensorCtx {
+    let mut ctx = SensorCtx::new();
+    ctx.init().unwrap();
+    ctx
+}
+
+// Contenu réel du stockage : IDs relus sur le capteur, templates exportés
+fn store(ctx: &mut SensorCtx) -> Vec<(u16, Vec<u8>)> {
+    let ids = ctx.refresh_cache().unwrap();
+    ids.into_iter()
+        .map(|id| (id, ctx.export_template(id).unwrap()))
+        .collect()
+}
+
+// ======================================================
+// Enrôlement tout ou rien (`enroll_user`)
+// ======================================================
+
+// `fault` sur un slot 1 vide puis occupé : l'enrôlement échoue et le stockage est
+// identique à avant
+fn assert_enroll_rolls_back(fault: SimFault) {
+    for occupied in [false, true] {
+        let _sim = sim();
+        sim_seed_template(2);
+        if occupied {
+            sim_seed_template(1);
+        }
+        let mut ctx = ready_ctx();
+        ctx.set_enroll_preparation(EnrollPreparation::Calibrate)
+            .unwrap();
+        let before = store(&mut ctx);
+
+        sim_fail_next(fault);
+        assert!(
+            ctx.enroll_user().is_err(),
+            "{fault:?}, slot occupé : {occupied}"
+        );
+        assert_eq!(
+            store(&mut ctx),
+            before,
+            "{fault:?}, slot occupé : {occupied}"
+        );
+    }
+}
+
+#[test]
+fn enroll_rolls_back_on_calibrate_failure() {
+    assert_enroll_rolls_back(SimFault::Calibrate);
+}
+
+#[test]
+fn enroll_rolls_back_on_enroll_start_failure() {
+    assert_enroll_rolls_back(SimFault::EnrollStart);
+}
+
+#[test]
+fn enroll_rolls_back_on_enroll_add_failure() {
+    assert_enroll_rolls_back(SimFault::EnrollAdd);
+}
+
+#[test]
+fn enroll_rolls_back_orphaned_save() {
+    assert_enroll_rolls_back(SimFault::TemplateSave);
+}
+
+#[test]
+fn enroll_rolls_back_on_confirm_failure() {
+    assert_enroll_rolls_back(SimFault::TemplateIds);
+}
+
+// `enroll_user` n'identifie pas : la panne d'identification vise le scan de
+// vérification de `enroll_and_verify`, qui restaure le slot de la même façon
+#[test]
+fn enroll_and_verify_rolls_back_on_identify_failure() {
+    for occupied in [false, true] {
+        let _sim = sim();
+        if occupied {
+            sim_seed_template(1);
+        }
+        let mut ctx = ready_ctx();
+        let before = store(&mut ctx);
+
+        sim_fail_next(SimFault::Identify);
+        assert!(
+            ctx.enroll_and_verify(1, 1000).is_err(),
+            "slot occupé : {occupied}"
+        );
+        assert_eq!(store(&mut ctx), before, "slot occupé : {occupied}");
+    }
+}
+
+// Vérification reconnue comme un autre template : enrôlement annulé, sans compter de
+// match pour cet autre template
+#[test]
+fn enroll_and_verify_wrong_match_leaves_no_trace() {
+    let _sim = sim();
+    sim_seed_template(2);
+    sim_set_finger(SimFinger::Template(2));
+    let mut ctx = ready_ctx();
+    let before = store(&mut ctx);
+
+    let err = ctx.enroll_and_verify(1, 1000).unwrap_err();
+    assert!(matches!(
+        FingerprintError::of(&err),
+        Some(FingerprintError::VerificationFailed {
+            id: 1,
+            matched: Some(2)
+        })
+    ));
+    assert_eq!(store(&mut ctx), before);
+    assert_eq!(ctx.template_stats(2).map_or(0, |s| s.match_count), 0);
+    assert_eq!(ctx.snapshot().last_match, None);
+}
+
+#[test]
+fn enroll_succeeds_after_rollback() {
+    let _sim = sim();
+    let mut ctx = ready_ctx();
+    sim_fail_next(SimFault::TemplateSave);
+    assert!(ctx.enroll_user().is_err());
+    assert_eq!(ctx.enroll_user().unwrap().template_id, 1);
+    assert_eq!(ctx.refresh_cache().unwrap(), [1]);
+}
+
+// `Overwrite` ne doit pas écraser un template 1 épinglé (compte admin)
+#[test]
+fn overwrite_keeps_pinned_user_template() {
+    let _sim = sim();
+    sim_seed_template(1);
+    sim_seed_template(2);
+    let mut ctx = ready_ctx();
+    ctx.pin_template(1).unwrap();
+    let before = store(&mut ctx);
+
+    let err = ctx
+        .enroll_user_if_needed(ExistingPolicy::Overwrite)
+        .unwrap_err();
+    assert!(matches!(
+        FingerprintError::of(&err),
+        Some(FingerprintError::AlreadyEnrolled)
+    ));
+    assert_eq!(store(&mut ctx), before);
+}
+
+// ======================================================
+// Enrôlement resté ouvert avant un reboot
+// ======================================================
+
+#[test]
+fn init_aborts_wedged_enroll() {
+    let _sim = sim();
+    sim_wedge_enroll();
+    let mut ctx = ready_ctx();
+    assert_eq!(ctx.enroll_user().unwrap().template_id, 1);
+    assert_eq!(ctx.refresh_cache().unwrap(), [1]);
+}
+
+// ======================================================
+// Attente de doigt interruptible
+// ======================================================
+
+// Dépassement toléré sur un délai, ordonnancement de l'hôte compris
+const WAIT_TOLERANCE_MS: u128 = 50;
+
+#[test]
+fn cancellable_wait_times_out_on_time() {
+    let _sim = sim();
+    sim_set_finger(SimFinger::Absent);
+    let mut ctx = ready_ctx();
+
+    // Pas un multiple de la tranche d'attente : la dernière est raccourcie. Échéance
+    // calculée en ms entières : jusqu'à 1 ms d'avance sur la mesure
+    let start = Instant::now();
+    let found = ctx
+        .wait_for_finger_cancellable(350, &CancelToken::new())
+        .unwrap();
+    let elapsed = start.elapsed().as_millis();
+    assert!(!found);
+    assert!(
+        (349..350 + WAIT_TOLERANCE_MS).contains(&elapsed),
+        "{elapsed} ms"
+    );
+}
+
+#[test]
+fn cancellable_wait_stops_on_cancel() {
+    let _sim = sim();
+    sim_set_finger(SimFinger::Absent);
+    let mut ctx = ready_ctx();
+
+    let cancel = CancelToken::new();
+    let canceller = {
+        let cancel = cancel.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(150));
+            cancel.cancel();
+        })
+    };
+    let start = Instant::now();
+    let res = ctx.wait_for_finger_cancellable(10_000, &cancel);
+    let elapsed = start.elapsed().as_millis();
+    canceller.join().unwrap();
+
+    let err = res.unwrap_err();
+    assert!(matches!(
+        FingerprintError::of(&err),
+        Some(FingerprintError::Cancelled)
+    ));
+    // Au plus une tranche d'attente firmware (100 ms) après l'annulation
+    assert!(elapsed < 150 + 100 + WAIT_TOLERANCE_MS, "{elapsed} ms");
+}
+
+#[test]
+fn cancellable_wait_returns_when_finger_present() {
+    let _sim = sim();
+    let mut ctx = ready_ctx();
+    assert!(ctx
+        .wait_for_finger_cancellable(1000, &CancelToken::new())
+        .unwrap());
+
+    let cancel = CancelToken::new();
+    cancel.cancel();
+    assert!(ctx.wait_for_finger_cancellable(1000, &cancel).is_err());
+}
+
+// ======================================================
+// Capteur non calibré
+// ======================================================
+
+#[test]
+fn identify_reports_not_calibrated() {
+    let _sim = sim();
+    sim_seed_template(1);
+    let mut ctx = ready_ctx();
+
+    sim_fail_next(SimFault::Uncalibrated);
+    let err = ctx.check_once(1000).unwrap_err();
+    assert!(matches!(
+        FingerprintError::of(&err),
+        Some(FingerprintError::NotCalibrated(_))
+    ));
+}
+
+#[test]
+fn identify_auto_calibrates_and_retries() {
+    let _sim = sim();
+    sim_seed_template(1);
+    let mut ctx = ready_ctx();
+    ctx.set_auto_calibrate(true).unwrap();
+
+    sim_fail_next(SimFault::Uncalibrated);
+    assert!(ctx.check_once(1000).unwrap());
+    assert_eq!(ctx.snapshot().last_match, Some(1));
+}
+
+// ======================================================
+// Horloge simulée : verrouillage, pauses et budget
+// ======================================================
+
+const LOCKOUT: LockoutPolicy = LockoutPolicy {
+    max_failures: 2,
+    window_ms: 10_000,
+    lockout_ms: 30_000,
+};
+
+fn locked_out_for(ctx: &mut SensorCtx) -> Option<u32> {
+    match ctx.check_once(1000) {
+        Ok(_) => None,
+        Err(e) => match FingerprintError::of(&e) {
+            Some(FingerprintError::LockedOut { retry_in_ms }) => Some(retry_in_ms),
+            _ => panic!("{e}"),
+        },
+    }
+}
+
+#[test]
+fn lockout_expires_on_mock_clock() {
+    let _sim = sim();
+    sim_seed_template(1);
+    sim_set_finger(SimFinger::Unknown);
+    let mut ctx = ready_ctx();
+    let clock = MockClock::new();
+    ctx.set_clock(clock.clone()).unwrap();
+    ctx.set_lockout_policy(LOCKOUT).unwrap();
+
+    assert!(!ctx.check_once(1000).unwrap());
+    assert!(!ctx.check_once(1000).unwrap());
+    assert_eq!(locked_out_for(&mut ctx), Some(30_000));
+
+    clock.advance(29_999);
+    assert_eq!(locked_out_for(&mut ctx), Some(1));
+    clock.advance(1);
+    assert_eq!(locked_out_for(&mut ctx), None);
+    assert_eq!(ctx.security_state().lockouts, 1);
+}
+
+#[test]
+fn failures_outside_window_start_a_new_series() {
+    let _sim = sim();
+    sim_seed_template(1);
+    sim_set_finger(SimFinger::Unknown);
+    let mut ctx = ready_ctx();
+    let clock = MockClock::new();
+    ctx.set_clock(clock.clone()).unwrap();
+    ctx.set_lockout_policy(LOCKOUT).unwrap();
+
+    assert!(!ctx.check_once(1000).unwrap());
+    clock.advance(10_001);
+    assert!(!ctx.check_once(1000).unwrap());
+    assert_eq!(ctx.security_state().consecutive_failures, 1);
+    assert_eq!(locked_out_for(&mut ctx), None);
+}
+
+// Passer de l'horloge système à une `MockClock` neuve (en retard sur elle) en pleine
+// série : pas de débordement, le verrouillage garde sa durée restante
+#[test]
+fn set_clock_carries_lockout_over() {
+    let _sim = sim();
+    sim_seed_template(1);
+    sim_set_finger(SimFinger::Unknown);
+    let mut ctx = ready_ctx();
+    ctx.set_lockout_policy(LOCKOUT).unwrap();
+
+    assert!(!ctx.check_once(1000).unwrap());
+    ctx.set_clock(MockClock::new()).unwrap();
+    assert_eq!(ctx.security_state().consecutive_failures, 0);
+    assert!(!ctx.check_once(1000).unwrap());
+    assert!(!ctx.check_once(1000).unwrap());
+    assert!(locked_out_for(&mut ctx).is_some());
+
+    let clock = MockClock::new();
+    clock.advance(5);
+    ctx.set_clock(clock.clone()).unwrap();
+    assert_eq!(ctx.security_state().lockout_remaining_ms, 30_000);
+    clock.advance(30_000);
+    assert_eq!(locked_out_for(&mut ctx), None);
+}
+
+#[test]
+fn identify_backoff_pauses_on_mock_clock() {
+    let _sim = sim();
+    sim_seed_template(1);
+    sim_set_finger(SimFinger::Unknown);
+    let mut ctx = ready_ctx();
+    let clock = MockClock::new();
+    ctx.set_clock(clock.clone()).unwrap();
+    ctx.set_identify_policy(IdentifyPolicy {
+        attempts: 3,
+        backoff_ms: 500,
+        budget_ms: 0,
+    })
+    .unwrap();
+
+    assert!(!ctx.check_once(1000).unwrap());
+    // Deux pauses entre trois tentatives
+    assert_eq!(clock.now_ms(), 1000);
+}
+
+#[test]
+fn identify_budget_bounds_backoff_on_mock_clock() {
+    let _sim = sim();
+    sim_seed_template(1);
+    sim_set_finger(SimFinger::Unknown);
+    let mut ctx = ready_ctx();
+    let clock = MockClock::new();
+    ctx.set_clock(clock.clone()).unwrap();
+    ctx.set_identify_policy(IdentifyPolicy {
+        attempts: 5,
+        backoff_ms: 500,
+        budget_ms: 700,
+    })
+    .unwrap();
+
+    // 500 ms de pause, puis les 200 ms restantes ; budget épuisé avant la 3e tentative
+    assert!(!ctx.check_once(1000).unwrap());
+    assert_eq!(clock.now_ms(), 700);
+}
+
+// ======================================================
+// Singleton global : requêtes sans attendre l'identification
+// ======================================================
+
+#[test]
+fn global_queries_do_not_wait_for_identify() {
+    let _sim = sim();
+    sim_seed_template(1);
+    fingerprint::init().unwrap();
+    fingerprint::set_label(1, "admin");
+
+    // Identification qui tient le verrou capteur 1 s (aucun doigt)
+    sim_set_finger(SimFinger::Absent);
+    let identify = thread::spawn(|| fingerprint::check_once(1000));
+    thread::sleep(Duration::from_millis(100));
+
+    let start = Instant::now();
+    assert!(fingerprint::is_user_enrolled().unwrap());
+    assert!(fingerprint::is_single_user().unwrap());
+    assert_eq!(fingerprint::enrolled_ids().unwrap(), [1]);
+    assert_eq!(
+        fingerprint::list_templates().unwrap(),
+        [(1, Some("admin".into()))]
+    );
+    assert!(start.elapsed() < Duration::from_millis(100));
+
+    assert!(!identify.join().unwrap().unwrap_or(false));
+    fingerprint::deinit().unwrap();
+}
+
+// ======================================================
+// Persistance des métadonnées (feature `nvs`)
+// ======================================================
+
+#[cfg(feature = "nvs")]
+const STATS: fingerprint::TemplateStats = fingerprint::TemplateStats {
+    enrolled_at: Some(1_700_000_000),
+    last_matched_at: None,
+    match_count: 3,
+};
+
+// Un exemplaire de chaque métadonnée, écrit en NVS ; renvoie le blob
+#[cfg(feature = "nvs")]
+fn flushed_metadata() -> Vec<u8> {
+    sim_seed_template(1);
+    sim_seed_template(2);
+    let mut ctx = ready_ctx();
+    ctx.set_label(1, "admin");
+    ctx.pin_template(1).unwrap();
+    ctx.set_template_quality(1, 80);
+    ctx.set_template_checksum(2, 0xDEAD_BEEF);
+    ctx.set_group(5, &[1, 2]).unwrap();
+    ctx.set_template_stats(2, STATS);
+    assert!(ctx.flush_nvs().unwrap());
+    // Blob inchangé : pas de réécriture
+    assert!(!ctx.flush_nvs().unwrap());
+    fingerprint::sim_nvs_blob().unwrap()
+}
+
+#[cfg(feature = "nvs")]
+#[test]
+fn nvs_metadata_round_trip() {
+    let _sim = sim();
+    flushed_metadata();
+
+    let mut ctx = ready_ctx();
+    assert_eq!(ctx.label(1).as_deref(), Some("admin"));
+    assert_eq!(ctx.pinned_templates(), [1]);
+    assert_eq!(ctx.template_quality(1), Some(80));
+    // Référence restaurée (fausse) : le template 2 est signalé
+    assert_eq!(ctx.verify_store_integrity().unwrap(), [2]);
+    assert_eq!(ctx.template_groups(), [(5, vec![1, 2])]);
+    assert_eq!(ctx.template_stats(2), Some(STATS));
+}
+
+// Blob illisible : `init` réussit, les métadonnées déjà en RAM restent intactes et
+// la prochaine écriture remplace le blob
+#[cfg(feature = "nvs")]
+fn assert_blob_rejected(blob: Vec<u8>) {
+    fingerprint::sim_set_nvs_blob(Some(blob));
+    let mut ctx = SensorCtx::new();
+    ctx.set_label(2, "kept");
+    ctx.init().unwrap();
+    assert_eq!(ctx.label(1), None);
+    assert_eq!(ctx.label(2).as_deref(), Some("kept"));
+    assert!(ctx.pinned_templates().is_empty());
+    assert!(ctx.flush_nvs().unwrap());
+}
+
+#[cfg(feature = "nvs")]
+#[test]
+fn nvs_truncated_blob_is_ignored() {
+    let _sim = sim();
+    let blob = flushed_metadata();
+    // Plus court que l'en-tête, puis coupé au milieu des entrées
+    for len in [5, blob.len() / 2, blob.len() - 1] {
+        assert_blob_rejected(blob[..len].to_vec());
+    }
+}
+
+#[cfg(feature = "nvs")]
+#[test]
+fn nvs_bad_crc_is_ignored() {
+    let _sim = sim();
+    let blob = flushed_metadata();
+    // Un octet altéré dans le contenu, puis dans le CRC final
+    for pos in [blob.len() / 2, blob.len() - 1] {
+        let mut corrupt = blob.clone();
+        corrupt[pos] ^= 0x01;
+        assert_blob_rejected(corrupt);
+    }
+}